@@ -0,0 +1,48 @@
+extern crate escposify;
+
+use std::env;
+use std::io;
+
+use escposify::emulator::Emulator;
+use escposify::printer::{CutMode, Printer};
+
+fn main() -> io::Result<()> {
+    let path = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "receipt.png".to_string());
+
+    let mut emulator = Emulator::new(576);
+    {
+        let mut printer = Printer::new(&mut emulator, None, None);
+        printer
+            .chain_align("ct")?
+            .chain_size(2, 2)?
+            .chain_println("RECEIPT")?
+            .chain_size(1, 1)?
+            .chain_style("b")?
+            .chain_println("Bold")?
+            .chain_style("u")?
+            .chain_println("Underlined")?
+            .chain_style("normal")?
+            .chain_reverse(true)?
+            .chain_println(" Reverse ")?
+            .chain_reverse(false)?
+            .chain_align("lt")?
+            .chain_font("B")?
+            .chain_println("Font B, the quick brown fox jumps over the lazy dog")?
+            .chain_font("A")?
+            .chain_println("Font A, the quick brown fox jumps over the lazy dog")?
+            .chain_align("rt")?
+            .chain_println("Total: 12.50")?
+            .chain_align("ct")?
+            .chain_barcode("12345670", "EAN8", "BLW", "A", 2, 50)?
+            .chain_barcode("ABC-123", "CODE39", "BLW", "B", 2, 50)?
+            .chain_feed(1)?
+            .chain_cut(CutMode::Partial, Some(3))?
+            .flush()?;
+    }
+    emulator
+        .image()
+        .save(&path)
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))
+}
@@ -1,7 +1,7 @@
 use std::fs::File;
 use std::io;
 
-use escposify::printer::Printer;
+use escposify::printer::{CutMode, Printer};
 
 fn main() -> io::Result<()> {
     let device_file = File::options().append(true).open("/dev/usb/lp0").unwrap();
@@ -13,6 +13,6 @@ fn main() -> io::Result<()> {
         .chain_size(0, 0)?
         .chain_text("The quick brown fox jumps over the lazy dog")?
         .chain_feed(1)?
-        .chain_cut(false)?
+        .chain_cut(CutMode::Full, Some(0))?
         .flush()
 }
@@ -1,7 +1,7 @@
 use std::io;
 
 use escposify::device::File;
-use escposify::printer::Printer;
+use escposify::printer::{CutMode, Printer};
 
 use tempfile::NamedTempFileOptions;
 
@@ -20,6 +20,6 @@ fn main() -> io::Result<()> {
         .chain_text("敏捷的棕色狐狸跳过懒狗")?
         .chain_barcode("12345678", "EAN8", "", "", 0, 0)?
         .chain_feed(1)?
-        .chain_cut(false)?
+        .chain_cut(CutMode::Full, Some(0))?
         .flush()
 }
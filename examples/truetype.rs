@@ -0,0 +1,29 @@
+use std::env;
+use std::io;
+
+use escposify::device::File;
+use escposify::printer::Printer;
+use escposify::ttf::TextRenderer;
+
+use tempfile::NamedTempFileOptions;
+
+fn main() -> io::Result<()> {
+    let font_path = env::args()
+        .nth(1)
+        .expect("usage: truetype <font.ttf> [text]");
+    let text = env::args()
+        .nth(2)
+        .unwrap_or_else(|| "שלום עולם! Hello world".to_string());
+
+    let tempf = NamedTempFileOptions::new().create().unwrap();
+    let file = File::from(tempf);
+    let mut printer = Printer::new(file, None, None);
+
+    let renderer = TextRenderer::from_path(font_path, 32.0, printer.profile().dot_width)?;
+    let image = renderer.render(&text);
+    printer
+        .chain_align("ct")?
+        .chain_raster(&image, None)?
+        .chain_feed(1)?
+        .flush()
+}
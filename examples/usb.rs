@@ -1,7 +1,7 @@
 use std::io;
 
 use escposify::device::Usb;
-use escposify::printer::Printer;
+use escposify::printer::{CutMode, Printer};
 
 fn main() -> io::Result<()> {
     let product_id = 0xa700; // find the correct product_id for your printer
@@ -19,6 +19,6 @@ fn main() -> io::Result<()> {
         .chain_text("The quick brown fox jumps over the lazy dog")?
         .chain_barcode("12345678", "EAN8", "", "", 0, 0)?
         .chain_feed(5)?
-        .chain_cut(false)?
+        .chain_cut(CutMode::Full, Some(0))?
         .flush()
 }
@@ -0,0 +1,195 @@
+//! Bar and space widths of the `GS k` barcode symbologies
+
+use crate::command::BarcodeKind;
+
+/// Modules of the EAN / UPC digits in the `L` set, `R` is the complement
+/// and `G` the reversed complement
+const EAN_L: [&str; 10] = [
+    "0001101", "0011001", "0010011", "0111101", "0100011", "0110001", "0101111", "0111011",
+    "0110111", "0001011",
+];
+/// Sets of the left half of EAN-13 by the first digit
+const EAN13_PARITY: [&str; 10] = [
+    "LLLLLL", "LLGLGG", "LLGGLG", "LLGGGL", "LGLLGG", "LGGLLG", "LGGGLL", "LGLGLG", "LGLGGL",
+    "LGGLGL",
+];
+/// Sets of UPC-E number system 0 by the check digit, swapped for 1
+const UPCE_PARITY: [&str; 10] = [
+    "GGGLLL", "GGLGLL", "GGLLGL", "GGLLLG", "GLGGLL", "GLLGGL", "GLLLGG", "GLGLGL", "GLGLLG",
+    "GLLGLG",
+];
+const CODE39_CHARS: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ-. $/+%*";
+/// Wide elements of the CODE39 characters, first element in bit 8
+const CODE39: [u16; 44] = [
+    0x034, 0x121, 0x061, 0x160, 0x031, 0x130, 0x070, 0x025, 0x124, 0x064, 0x109, 0x049, 0x148,
+    0x019, 0x118, 0x058, 0x00d, 0x10c, 0x04c, 0x01c, 0x103, 0x043, 0x142, 0x013, 0x112, 0x052,
+    0x007, 0x106, 0x046, 0x016, 0x181, 0x0c1, 0x1c0, 0x091, 0x190, 0x0d0, 0x085, 0x184, 0x0c4,
+    0x0a8, 0x0a2, 0x08a, 0x02a, 0x094,
+];
+/// Wide elements of the ITF digits, first element in bit 4
+const ITF: [u16; 10] = [
+    0b00110, 0b10001, 0b01001, 0b11000, 0b00101, 0b10100, 0b01100, 0b00011, 0b10010, 0b01010,
+];
+const NW7_CHARS: &str = "0123456789-$:/.+ABCD";
+/// Wide elements of the NW-7 (Codabar) characters, first element in bit 6
+const NW7: [u16; 20] = [
+    0b0000011, 0b0000110, 0b0001001, 0b1100000, 0b0010010, 0b1000010, 0b0100001, 0b0100100,
+    0b0110000, 0b1001000, 0b0001100, 0b0011000, 0b1000101, 0b1010001, 0b1010100, 0b0010101,
+    0b0011010, 0b0101001, 0b0001011, 0b0001110,
+];
+
+fn check_digit(digits: &[u8]) -> u8 {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| d as u32 * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+    ((10 - sum % 10) % 10) as u8
+}
+
+fn ean_digit(d: u8, set: char) -> String {
+    let modules = EAN_L[d as usize].chars();
+    let invert = |c| if c == '0' { '1' } else { '0' };
+    match set {
+        'L' => modules.collect(),
+        'R' => modules.map(invert).collect(),
+        _ => modules.rev().map(invert).collect(),
+    }
+}
+
+/// Bar and space widths of a module string, starting with a bar
+fn runs(modules: &str, narrow: u32) -> Vec<u32> {
+    let mut widths: Vec<u32> = vec![];
+    let mut last = '0';
+    for module in modules.chars() {
+        match widths.last_mut() {
+            Some(width) if module == last => *width += narrow,
+            _ => widths.push(narrow),
+        }
+        last = module;
+    }
+    widths
+}
+
+/// Element widths of `count` elements, wide where `pattern` has a bit set
+fn elements(pattern: u16, count: u32, narrow: u32, wide: u32) -> impl Iterator<Item = u32> {
+    (0..count).rev().map(move |bit| {
+        if pattern >> bit & 1 == 1 {
+            wide
+        } else {
+            narrow
+        }
+    })
+}
+
+/// Bar and space widths in dots and the human readable text of a barcode,
+/// `None` for data the printer would reject
+pub(crate) fn bars(kind: BarcodeKind, code: &str, narrow: u32) -> Option<(Vec<u32>, String)> {
+    let wide = (narrow * 5 + 1) / 2;
+    let digits: Vec<u8> = code.bytes().map(|b| b.wrapping_sub(b'0')).collect();
+    let numeric = digits.iter().all(|&d| d < 10);
+    let ean = |digits: &[u8], parity: &str| {
+        let half = digits.len() / 2;
+        let mut modules = "101".to_string();
+        for (&d, set) in digits[..half].iter().zip(parity.chars()) {
+            modules.push_str(&ean_digit(d, set));
+        }
+        modules.push_str("01010");
+        for &d in &digits[half..] {
+            modules.push_str(&ean_digit(d, 'R'));
+        }
+        modules.push_str("101");
+        modules
+    };
+    let with_check = |digits: &[u8], len: usize| {
+        let mut digits = digits[..len].to_vec();
+        digits.push(check_digit(&digits));
+        digits
+    };
+    let text = |digits: &[u8]| digits.iter().map(|d| (b'0' + d) as char).collect();
+    let (modules, hri) = match kind {
+        BarcodeKind::UpcA if numeric && (digits.len() == 11 || digits.len() == 12) => {
+            let digits = with_check(&digits, 11);
+            (ean(&digits, "LLLLLL"), text(&digits))
+        }
+        BarcodeKind::Ean13 if numeric && (digits.len() == 12 || digits.len() == 13) => {
+            let digits = with_check(&digits, 12);
+            (
+                ean(&digits[1..], EAN13_PARITY[digits[0] as usize]),
+                text(&digits),
+            )
+        }
+        BarcodeKind::Ean8 if numeric && (digits.len() == 7 || digits.len() == 8) => {
+            let digits = with_check(&digits, 7);
+            (ean(&digits, "LLLL"), text(&digits))
+        }
+        BarcodeKind::UpcE if numeric && (6..=8).contains(&digits.len()) => {
+            let digits = if digits.len() == 6 {
+                [&[0], &digits[..]].concat()
+            } else {
+                digits[..7].to_vec()
+            };
+            if digits[0] > 1 {
+                return None;
+            }
+            // The check digit is the one of the UPC-A code
+            let d = &digits[1..];
+            let upc_a = match d[5] {
+                0..=2 => [d[0], d[1], d[5], 0, 0, 0, 0, d[2], d[3], d[4]],
+                3 => [d[0], d[1], d[2], 0, 0, 0, 0, 0, d[3], d[4]],
+                4 => [d[0], d[1], d[2], d[3], 0, 0, 0, 0, 0, d[4]],
+                _ => [d[0], d[1], d[2], d[3], d[4], 0, 0, 0, 0, d[5]],
+            };
+            let check = check_digit(&[&digits[..1], &upc_a[..]].concat());
+            let mut modules = "101".to_string();
+            for (&d, set) in digits[1..].iter().zip(UPCE_PARITY[check as usize].chars()) {
+                let set = match (digits[0], set) {
+                    (1, 'L') => 'G',
+                    (1, _) => 'L',
+                    (_, set) => set,
+                };
+                modules.push_str(&ean_digit(d, set));
+            }
+            modules.push_str("010101");
+            let mut digits = digits;
+            digits.push(check);
+            (modules, text(&digits))
+        }
+        BarcodeKind::Code39 => {
+            let code = code.trim_matches('*');
+            let mut widths = vec![];
+            for c in format!("*{}*", code).chars() {
+                let i = CODE39_CHARS.find(c.to_ascii_uppercase())?;
+                if !widths.is_empty() {
+                    widths.push(narrow);
+                }
+                widths.extend(elements(CODE39[i], 9, narrow, wide));
+            }
+            return Some((widths, format!("*{}*", code)));
+        }
+        BarcodeKind::Itf if numeric && !digits.is_empty() && digits.len() % 2 == 0 => {
+            let mut widths = vec![narrow; 4];
+            for pair in digits.chunks(2) {
+                let bars = elements(ITF[pair[0] as usize], 5, narrow, wide);
+                let spaces = elements(ITF[pair[1] as usize], 5, narrow, wide);
+                widths.extend(bars.zip(spaces).flat_map(|(bar, space)| [bar, space]));
+            }
+            widths.extend([wide, narrow, narrow]);
+            return Some((widths, code.to_string()));
+        }
+        BarcodeKind::Nw7 => {
+            let mut widths = vec![];
+            for c in code.chars() {
+                let i = NW7_CHARS.find(c.to_ascii_uppercase())?;
+                if !widths.is_empty() {
+                    widths.push(narrow);
+                }
+                widths.extend(elements(NW7[i], 7, narrow, wide));
+            }
+            return Some((widths, code.to_string()));
+        }
+        _ => return None,
+    };
+    Some((runs(&modules, narrow), hri))
+}
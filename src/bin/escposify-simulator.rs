@@ -0,0 +1,48 @@
+//! Printer simulator for testing network printing
+//!
+//! Listens on `127.0.0.1:9100` (or the given address) and prints a listing
+//! of every job it receives.
+
+use std::env;
+use std::io;
+use std::process;
+
+use escposify::decoder;
+use escposify::simulator::{Simulator, Status};
+
+const USAGE: &str = "usage: escposify-simulator [ADDRESS] [--paper-out] [--paper-near-end] \
+                     [--cover-open] [--drawer-open] [--cutter-error]";
+
+fn main() -> io::Result<()> {
+    let mut addr = "127.0.0.1:9100".to_string();
+    let mut status = Status::default();
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--paper-out" => status.paper_out = true,
+            "--paper-near-end" => status.paper_near_end = true,
+            "--cover-open" => status.cover_open = true,
+            "--drawer-open" => status.drawer_open = true,
+            "--cutter-error" => status.cutter_error = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ if arg.starts_with('-') => {
+                eprintln!("Unknown option: {}\n{}", arg, USAGE);
+                process::exit(2);
+            }
+            _ => addr = arg,
+        }
+    }
+
+    let simulator = Simulator::start(addr.as_str())?;
+    simulator.set_status(status);
+    println!("Listening on {}", simulator.local_addr());
+    for count in 1.. {
+        let jobs = simulator.wait_for_jobs(count, None);
+        let job = &jobs[count - 1];
+        println!("Job {} ({} bytes)", count, job.data.len());
+        print!("{}", decoder::listing(&job.entries()));
+    }
+    Ok(())
+}
@@ -0,0 +1,205 @@
+//! Printer method calls as data, the format of the node bridge
+//!
+//! A [Call] names a [Printer][crate::printer::Printer] method and lists its
+//! arguments, e.g. `{"name": "barcode", "args": ["12345678", "EAN8", "",
+//! "", 0, 0]}`. [Document]s deserialize from a list mixing calls and
+//! [Command]s.
+//!
+//! ```rust
+//! use escposify::command::{Command, Document};
+//!
+//! let json = r#"[
+//!     {"name": "align", "args": ["ct"]},
+//!     {"name": "text", "args": ["Hello"]},
+//!     {"bold": true},
+//!     {"name": "cut", "args": [false]}
+//! ]"#;
+//! let document: Document = serde_json::from_str(json).unwrap();
+//! assert_eq!(document.commands[1], Command::Text("Hello\n".to_string()));
+//! ```
+
+use std::io;
+
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::command::{
+    BitImageDensity, Command, Control, CutMode, Document, DrawerPin, KanjiEncoding, QrLevel,
+    RasterMode,
+};
+use crate::img::Image;
+use crate::style::{Alignment, Font};
+
+/// A printer method name and its arguments
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Call {
+    /// Name of the printer method, e.g. "text"
+    pub name: String,
+    #[serde(default)]
+    pub args: Vec<Value>,
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl Call {
+    pub fn new(name: &str, args: Vec<Value>) -> Call {
+        Call {
+            name: name.to_string(),
+            args,
+        }
+    }
+
+    fn arg(&self, i: usize) -> Option<&Value> {
+        self.args.get(i).filter(|value| !value.is_null())
+    }
+
+    fn invalid_arg(&self, i: usize, expected: &str) -> io::Error {
+        invalid(format!(
+            "Argument {} of {} must be {}",
+            i + 1,
+            self.name,
+            expected
+        ))
+    }
+
+    fn str(&self, i: usize) -> io::Result<&str> {
+        self.arg(i)
+            .and_then(Value::as_str)
+            .ok_or_else(|| self.invalid_arg(i, "a string"))
+    }
+
+    /// Optional string argument, `default` if missing
+    fn str_or<'a>(&'a self, i: usize, default: &'a str) -> io::Result<&'a str> {
+        match self.arg(i) {
+            Some(_) => self.str(i),
+            None => Ok(default),
+        }
+    }
+
+    fn int(&self, i: usize) -> io::Result<i64> {
+        self.arg(i)
+            .and_then(Value::as_i64)
+            .ok_or_else(|| self.invalid_arg(i, "an integer"))
+    }
+
+    /// Integer argument in the range of `T`
+    fn num<T: TryFrom<i64>>(&self, i: usize) -> io::Result<T> {
+        T::try_from(self.int(i)?).map_err(|_| self.invalid_arg(i, "in range"))
+    }
+
+    /// Optional integer argument in the range of `T`, `default` if missing
+    fn num_or<T: TryFrom<i64>>(&self, i: usize, default: T) -> io::Result<T> {
+        match self.arg(i) {
+            Some(_) => self.num(i),
+            None => Ok(default),
+        }
+    }
+
+    fn bool(&self, i: usize) -> io::Result<bool> {
+        self.arg(i)
+            .and_then(Value::as_bool)
+            .ok_or_else(|| self.invalid_arg(i, "a boolean"))
+    }
+
+    fn image(&self, i: usize) -> io::Result<Image> {
+        let value = self
+            .arg(i)
+            .ok_or_else(|| self.invalid_arg(i, "a base64 encoded image"))?;
+        Image::deserialize(value).map_err(|err| invalid(err.to_string()))
+    }
+
+    /// Commands of the call, like the printer method of the same name
+    pub fn commands(&self) -> io::Result<Vec<Command>> {
+        let command = match self.name.as_ref() {
+            "hwinit" => Command::Init,
+            "hwselect" => Command::Select,
+            "hwreset" => Command::Reset,
+            "hardware" => match self.str(0)? {
+                "INIT" => Command::Init,
+                "SELECT" => Command::Select,
+                "RESET" => Command::Reset,
+                hw => return Err(invalid(format!("Invalid hardware command: {}", hw))),
+            },
+            "print" => Command::Text(self.str(0)?.to_string()),
+            "println" | "text" => Command::line(self.str(0)?),
+            "print_cjk" => Command::TextCjk(self.str(0)?.to_string()),
+            "text_cjk" => Command::TextCjk(format!("{}\n", self.str(0)?)),
+            "code_page" => Command::CodePage(self.num(0)?),
+            "set_charset" => Command::charset(self.str(0)?)?,
+            "kanji_encoding" => Command::KanjiEncoding(KanjiEncoding::parse(self.str(0)?)?),
+            "line_space" => Command::LineSpace(u8::try_from(self.int(0)?).ok()),
+            "feed" => Command::Feed(self.num(0)?),
+            "feed_dots" => Command::FeedDots(self.num(0)?),
+            "feed_lines" => Command::FeedLines(self.num(0)?),
+            "control" => Command::Control(Control::parse(self.str(0)?)?),
+            "align" => Command::Align(Alignment::parse(self.str(0)?)?),
+            "font" => Command::Font(Font::parse(self.str(0)?)?),
+            "style" => return Ok(Command::style(self.str(0)?).to_vec()),
+            "bold" => Command::Bold(self.bool(0)?),
+            "underline" => Command::Underline(self.num(0)?),
+            "reverse" => Command::Reverse(self.bool(0)?),
+            "upside_down" => Command::UpsideDown(self.bool(0)?),
+            "rotate" => Command::Rotate(self.bool(0)?),
+            "double_strike" => Command::DoubleStrike(self.bool(0)?),
+            "smoothing" => Command::Smoothing(self.bool(0)?),
+            "char_spacing" => Command::CharSpacing(self.num(0)?),
+            "second_color" => Command::SecondColor(self.bool(0)?),
+            "size" => Command::Size {
+                width: self.num(0)?,
+                height: self.num(1)?,
+            },
+            "barcode" => Command::barcode(
+                self.str(0)?,
+                self.str(1)?,
+                self.str(2)?,
+                self.str(3)?,
+                self.int(4)?.max(0) as usize,
+                self.int(5)?.max(0) as usize,
+            ),
+            "qrcode" => Command::QrCode {
+                code: self.str(0)?.to_string(),
+                version: self.num_or(1, 3)?,
+                level: QrLevel::from_name(self.str_or(2, "L")?),
+                size: self.num_or(3, 3)?,
+            },
+            "cashdraw" => Command::CashDraw(DrawerPin::new(self.num(0)?)?),
+            // The bridge's `cut(partial)` feeds 3 lines before cutting
+            "cut" => {
+                let mode = match self.arg(0) {
+                    Some(_) if self.bool(0)? => CutMode::Partial,
+                    _ => CutMode::Full,
+                };
+                return Ok(vec![Command::Feed(3), Command::Cut { mode, feed: None }]);
+            }
+            "image" => Command::Image(self.image(0)?),
+            "bit_image" => Command::BitImage {
+                image: self.image(0)?,
+                density: BitImageDensity::from_name(self.str_or(1, "D24")?),
+            },
+            "raster" => Command::Raster {
+                image: self.image(0)?,
+                mode: RasterMode::from_name(self.str_or(1, "NORMAL")?),
+            },
+            name => return Err(invalid(format!("Unknown printer method: {}", name))),
+        };
+        Ok(vec![command])
+    }
+}
+
+impl<'de> Deserialize<'de> for Document {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Document, D::Error> {
+        let mut document = Document::new();
+        for entry in Vec::<Value>::deserialize(deserializer)? {
+            // Objects with a method name are calls, anything else a command
+            if entry.get("name").is_some() {
+                let call = Call::deserialize(entry).map_err(de::Error::custom)?;
+                document.extend(call.commands().map_err(de::Error::custom)?);
+            } else {
+                document.push(Command::deserialize(entry).map_err(de::Error::custom)?);
+            }
+        }
+        Ok(document)
+    }
+}
@@ -0,0 +1,205 @@
+//! Code page tables and per-character code page selection (`ESC t`)
+//!
+//! Code pages are looked up by the labels used in the profile database.
+//! The IBM PC code pages missing from the `encoding` crate are built in,
+//! everything else is resolved through its WHATWG labels.
+
+use std::io;
+
+use encoding::label::encoding_from_whatwg_label;
+use encoding::types::{DecoderTrap, EncoderTrap, EncodingRef};
+
+// Upper halves (0x80-0xFF) of the built-in code pages, the lower halves are ASCII
+const CP437: &str = concat!(
+    "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜ¢£¥₧ƒ",
+    "áíóúñÑªº¿⌐¬½¼¡«»░▒▓│┤╡╢╖╕╣║╗╝╜╛┐",
+    "└┴┬├─┼╞╟╚╔╩╦╠═╬╧╨╤╥╙╘╒╓╫╪┘┌█▄▌▐▀",
+    "αßΓπΣσµτΦΘΩδ∞φε∩≡±≥≤⌠⌡÷≈°∙·√ⁿ²■\u{00a0}",
+);
+const CP850: &str = concat!(
+    "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜø£Ø×ƒ",
+    "áíóúñÑªº¿®¬½¼¡«»░▒▓│┤ÁÂÀ©╣║╗╝¢¥┐",
+    "└┴┬├─┼ãÃ╚╔╩╦╠═╬¤ðÐÊËÈıÍÎÏ┘┌█▄¦Ì▀",
+    "ÓßÔÒõÕµþÞÚÛÙýÝ¯´\u{00ad}±‗¾¶§÷¸°¨·¹³²■\u{00a0}",
+);
+const CP858: &str = concat!(
+    "ÇüéâäàåçêëèïîìÄÅÉæÆôöòûùÿÖÜø£Ø×ƒ",
+    "áíóúñÑªº¿®¬½¼¡«»░▒▓│┤ÁÂÀ©╣║╗╝¢¥┐",
+    "└┴┬├─┼ãÃ╚╔╩╦╠═╬¤ðÐÊËÈ€ÍÎÏ┘┌█▄¦Ì▀",
+    "ÓßÔÒõÕµþÞÚÛÙýÝ¯´\u{00ad}±‗¾¶§÷¸°¨·¹³²■\u{00a0}",
+);
+
+fn unmappable(c: char, trap: EncoderTrap, output: &mut Vec<u8>) -> io::Result<()> {
+    match trap {
+        EncoderTrap::Strict => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("No code page can encode {:?}", c),
+            ))
+        }
+        EncoderTrap::Ignore => {}
+        EncoderTrap::NcrEscape => output.extend_from_slice(format!("&#{};", c as u32).as_bytes()),
+        _ => output.push(b'?'),
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum Charset {
+    Table(&'static str),
+    Encoding(EncodingRef),
+}
+
+/// A character code table the printer switches to with `ESC t n`
+#[derive(Clone)]
+pub struct CodePage {
+    pub number: u8,
+    pub label: String,
+    charset: Charset,
+}
+
+impl CodePage {
+    /// Resolve the code page `label`, `None` if it is unknown
+    pub fn new(number: u8, label: &str) -> Option<CodePage> {
+        let charset = match label.to_lowercase().as_ref() {
+            "cp437" | "ibm437" => Charset::Table(CP437),
+            "cp850" | "ibm850" => Charset::Table(CP850),
+            "cp858" | "ibm858" => Charset::Table(CP858),
+            _ => Charset::Encoding(encoding_from_whatwg_label(label)?),
+        };
+        Some(CodePage {
+            number,
+            label: label.to_string(),
+            charset,
+        })
+    }
+
+    /// Encode a single character, `None` if the code page lacks it
+    pub fn encode_char(&self, c: char) -> Option<u8> {
+        if c.is_ascii() {
+            return Some(c as u8);
+        }
+        match self.charset {
+            Charset::Table(table) => table.chars().position(|x| x == c).map(|i| 0x80 + i as u8),
+            Charset::Encoding(codec) => {
+                let mut buf = [0; 4];
+                match codec.encode(c.encode_utf8(&mut buf), EncoderTrap::Strict) {
+                    Ok(bytes) if bytes.len() == 1 => Some(bytes[0]),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Encode `content` with this code page only, characters it lacks are
+    /// handled according to `trap`
+    pub fn encode(&self, content: &str, trap: EncoderTrap) -> io::Result<Vec<u8>> {
+        let mut rv = Vec::with_capacity(content.len());
+        for c in content.chars() {
+            match self.encode_char(c) {
+                Some(b) => rv.push(b),
+                None => unmappable(c, trap, &mut rv)?,
+            }
+        }
+        Ok(rv)
+    }
+
+    /// Decode `bytes` of this code page, unknown bytes become U+FFFD
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        match self.charset {
+            Charset::Table(table) => bytes
+                .iter()
+                .map(|&b| match b {
+                    0..=0x7f => b as char,
+                    _ => table.chars().nth(b as usize - 0x80).unwrap_or('\u{fffd}'),
+                })
+                .collect(),
+            Charset::Encoding(codec) => codec
+                .decode(bytes, DecoderTrap::Replace)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Whether the code page is the one used by `codec`
+    pub fn is_codec(&self, codec: EncodingRef) -> bool {
+        match self.charset {
+            Charset::Table(_) => false,
+            Charset::Encoding(own) => own.name() == codec.name(),
+        }
+    }
+}
+
+/// A run of encoded text and the code page to switch to before printing it
+/// (`None` when no switch is needed)
+pub type Run = (Option<u8>, Vec<u8>);
+
+/// The code page table of a printer
+#[derive(Clone, Default)]
+pub struct CodePages {
+    pages: Vec<CodePage>,
+}
+
+impl CodePages {
+    /// Build the table from `(number, label)` pairs, skipping unknown labels
+    pub fn new<'a, I: IntoIterator<Item = (u8, &'a str)>>(pages: I) -> CodePages {
+        CodePages {
+            pages: pages
+                .into_iter()
+                .filter_map(|(number, label)| CodePage::new(number, label))
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, number: u8) -> Option<&CodePage> {
+        self.pages.iter().find(|page| page.number == number)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CodePage> {
+        self.pages.iter()
+    }
+
+    /// Encode `content` with the current code page where possible, switching
+    /// to the page covering the most of the following characters otherwise.
+    /// Characters no page can encode are handled according to `trap`.
+    pub fn encode(
+        &self,
+        content: &str,
+        current: Option<u8>,
+        trap: EncoderTrap,
+    ) -> io::Result<Vec<Run>> {
+        let chars: Vec<char> = content.chars().collect();
+        let mut runs: Vec<Run> = vec![(None, vec![])];
+        let mut current = current.and_then(|number| self.get(number));
+        for (i, &c) in chars.iter().enumerate() {
+            if let Some(b) = current.and_then(|page| page.encode_char(c)) {
+                runs.last_mut().unwrap().1.push(b);
+                continue;
+            }
+            if c.is_ascii() {
+                runs.last_mut().unwrap().1.push(c as u8);
+                continue;
+            }
+            let coverage = |page: &CodePage| {
+                chars[i..]
+                    .iter()
+                    .filter(|c| !c.is_ascii())
+                    .take_while(|&&c| page.encode_char(c).is_some())
+                    .count()
+            };
+            let best = self
+                .pages
+                .iter()
+                .filter(|page| page.encode_char(c).is_some())
+                .max_by(|a, b| coverage(a).cmp(&coverage(b)).then(b.number.cmp(&a.number)));
+            match best {
+                Some(page) => {
+                    current = Some(page);
+                    runs.push((Some(page.number), vec![page.encode_char(c).unwrap()]));
+                }
+                None => unmappable(c, trap, &mut runs.last_mut().unwrap().1)?,
+            }
+        }
+        runs.retain(|(page, bytes)| page.is_some() || !bytes.is_empty());
+        Ok(runs)
+    }
+}
@@ -0,0 +1,649 @@
+//! Printer commands as data
+//!
+//! A [Document] is a list of [Command]s that can be inspected, reordered
+//! or built up front, then turned into ESC/POS bytes by an
+//! [Encoder][crate::encoder::Encoder] or sent with
+//! [Printer::print_document][crate::printer::Printer::print_document].
+//! Every [Printer][crate::printer::Printer] method is implemented as one
+//! or more commands.
+//!
+//! ```rust
+//! use escposify::command::{Command, Document};
+//! use escposify::printer::CutMode;
+//! use escposify::style::Alignment;
+//!
+//! let mut document = Document::new();
+//! document
+//!     .push(Command::Align(Alignment::Center))
+//!     .push(Command::Bold(true))
+//!     .push(Command::Text("RECEIPT\n".to_string()))
+//!     .push(Command::Cut { mode: CutMode::Full, feed: Some(0) });
+//! let bytes = document.encode().unwrap();
+//! assert_eq!(&bytes[..6], b"\x1b\x61\x01\x1b\x45\x01");
+//! ```
+
+use std::io;
+use std::slice;
+use std::time::Duration;
+use std::vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::consts;
+use crate::encoder::Encoder;
+use crate::img::Image;
+use crate::page::{Area, Direction};
+use crate::style::{Alignment, Font};
+
+/// How [Command::Cut] cuts the paper
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CutMode {
+    /// Full cut (`GS V 0`), or feed and full cut (`GS V 65`)
+    Full,
+    /// Partial cut (`GS V 1`), or feed and partial cut (`GS V 66`)
+    Partial,
+    /// Reserve a full cut at the cutting position plus `feed`, made once
+    /// printing reaches it without feeding extra paper (`GS V 97`)
+    ReservedFull,
+    /// Reserve a partial cut at the cutting position plus `feed` (`GS V 98`)
+    ReservedPartial,
+    /// Feed to the cutting position plus `feed`, full cut and feed back to
+    /// the print starting position (`GS V 103`)
+    ReturnFull,
+    /// Feed to the cutting position plus `feed`, partial cut and feed back
+    /// to the print starting position (`GS V 104`)
+    ReturnPartial,
+}
+
+/// Sound pattern of [Command::Buzzer] and [Command::ExternalBuzzer]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BuzzerPattern {
+    A,
+    B,
+    C,
+    D,
+    E,
+}
+
+impl BuzzerPattern {
+    pub(crate) fn value(self) -> u8 {
+        match self {
+            BuzzerPattern::A => 1,
+            BuzzerPattern::B => 2,
+            BuzzerPattern::C => 3,
+            BuzzerPattern::D => 4,
+            BuzzerPattern::E => 5,
+        }
+    }
+}
+
+/// When [Command::RunMacro] repeats the macro
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum MacroMode {
+    /// After waiting the given time
+    Continuous,
+    /// When the FEED button is pressed
+    FeedButton,
+}
+
+/// Multi-byte encoding of Kanji mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum KanjiEncoding {
+    /// JIS, selected with `FS C 0`
+    Jis,
+    /// Shift JIS, selected with `FS C 1`
+    ShiftJis,
+    Gb2312,
+    Big5,
+    Ksc5601,
+}
+
+impl KanjiEncoding {
+    /// Parse an encoding name ("JIS", "SJIS", "GB2312", "BIG5" or
+    /// "KSC5601")
+    pub fn parse(encoding: &str) -> io::Result<KanjiEncoding> {
+        match encoding.to_uppercase().replace('-', "_").as_ref() {
+            "JIS" => Ok(KanjiEncoding::Jis),
+            "SJIS" | "SHIFT_JIS" => Ok(KanjiEncoding::ShiftJis),
+            "GB2312" | "GBK" => Ok(KanjiEncoding::Gb2312),
+            "BIG5" => Ok(KanjiEncoding::Big5),
+            "KSC5601" | "KS_C_5601" | "EUC_KR" => Ok(KanjiEncoding::Ksc5601),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid Kanji encoding: {}", encoding),
+            )),
+        }
+    }
+}
+
+/// Control character of [Command::Control]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Control {
+    /// Line feed
+    Lf,
+    /// Form feed, also ends page mode
+    Ff,
+    /// Carriage return
+    Cr,
+    /// Horizontal tab
+    Ht,
+    /// Vertical tab
+    Vt,
+}
+
+impl Control {
+    /// Parse a control name ("LF", "FF", "CR", "HT" or "VT")
+    pub fn parse(ctrl: &str) -> io::Result<Control> {
+        match ctrl.to_uppercase().as_ref() {
+            "LF" => Ok(Control::Lf),
+            "FF" => Ok(Control::Ff),
+            "CR" => Ok(Control::Cr),
+            "HT" => Ok(Control::Ht),
+            "VT" => Ok(Control::Vt),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid control action: {}", ctrl),
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BarcodeKind {
+    UpcA,
+    UpcE,
+    Ean13,
+    Ean8,
+    Code39,
+    Itf,
+    Nw7,
+}
+
+impl BarcodeKind {
+    /// Parse a barcode type ("UPC_A", "UPC_E", "EAN13", "EAN8", "CODE39",
+    /// "ITF" or "NW7")
+    pub fn parse(kind: &str) -> io::Result<BarcodeKind> {
+        match kind.to_uppercase().replace('-', "_").as_ref() {
+            "UPC_A" => Ok(BarcodeKind::UpcA),
+            "UPC_E" => Ok(BarcodeKind::UpcE),
+            "EAN13" => Ok(BarcodeKind::Ean13),
+            "EAN8" => Ok(BarcodeKind::Ean8),
+            "CODE39" => Ok(BarcodeKind::Code39),
+            "ITF" => Ok(BarcodeKind::Itf),
+            "NW7" => Ok(BarcodeKind::Nw7),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid barcode type: {}", kind),
+            )),
+        }
+    }
+
+    /// Name used by [Profile::barcodes][crate::profile::Profile::barcodes]
+    pub fn name(self) -> &'static str {
+        match self {
+            BarcodeKind::UpcA => "UPC_A",
+            BarcodeKind::UpcE => "UPC_E",
+            BarcodeKind::Ean13 => "EAN13",
+            BarcodeKind::Ean8 => "EAN8",
+            BarcodeKind::Code39 => "CODE39",
+            BarcodeKind::Itf => "ITF",
+            BarcodeKind::Nw7 => "NW7",
+        }
+    }
+}
+
+/// Position of the human readable barcode text
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BarcodeText {
+    Off,
+    Above,
+    Below,
+    Both,
+}
+
+impl BarcodeText {
+    /// Parse a text position ("OFF", "ABV", "BLW" or "BTH"), below if
+    /// unknown
+    pub fn from_name(position: &str) -> BarcodeText {
+        match position.to_uppercase().as_ref() {
+            "OFF" => BarcodeText::Off,
+            "ABV" => BarcodeText::Above,
+            "BTH" => BarcodeText::Both,
+            // "BLW" | _ =>
+            _ => BarcodeText::Below,
+        }
+    }
+}
+
+/// QR code error correction level
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum QrLevel {
+    /// 7%
+    L,
+    /// 15%
+    M,
+    /// 25%
+    Q,
+    /// 30%
+    H,
+}
+
+impl QrLevel {
+    /// Parse a level ("L", "M", "Q" or "H"), L if unknown
+    pub fn from_name(level: &str) -> QrLevel {
+        match level.to_uppercase().as_ref() {
+            "M" => QrLevel::M,
+            "Q" => QrLevel::Q,
+            "H" => QrLevel::H,
+            // "L" | _ =>
+            _ => QrLevel::L,
+        }
+    }
+}
+
+/// Scaling of [Command::Raster] images
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RasterMode {
+    Normal,
+    DoubleWidth,
+    DoubleHeight,
+    Quadruple,
+}
+
+impl RasterMode {
+    /// Parse a mode ("NORMAL", "DW", "DH" or "DWDH"), normal if unknown
+    pub fn from_name(mode: &str) -> RasterMode {
+        match mode.to_uppercase().as_ref() {
+            "DH" => RasterMode::DoubleHeight,
+            "DWDH" => RasterMode::Quadruple,
+            "DW" => RasterMode::DoubleWidth,
+            // "NORMAL" | _ =>
+            _ => RasterMode::Normal,
+        }
+    }
+}
+
+/// Density of [Command::BitImage] images
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BitImageDensity {
+    /// 8 dots single density
+    S8,
+    /// 8 dots double density
+    D8,
+    /// 24 dots single density
+    S24,
+    /// 24 dots double density
+    D24,
+}
+
+impl BitImageDensity {
+    /// Parse a density ("S8", "D8", "S24" or "D24"), D24 if unknown
+    pub fn from_name(density: &str) -> BitImageDensity {
+        match density.to_uppercase().as_ref() {
+            "S8" => BitImageDensity::S8,
+            "D8" => BitImageDensity::D8,
+            "S24" => BitImageDensity::S24,
+            // "D24" | _ =>
+            _ => BitImageDensity::D24,
+        }
+    }
+
+    /// Vertical dots per line
+    pub fn dots(self) -> u32 {
+        match self {
+            BitImageDensity::S8 | BitImageDensity::D8 => 8,
+            BitImageDensity::S24 | BitImageDensity::D24 => 24,
+        }
+    }
+}
+
+/// Drawer kick-out connector pin
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DrawerPin {
+    Pin2,
+    Pin5,
+}
+
+impl DrawerPin {
+    /// The pin numbered `pin` (2 or 5)
+    pub fn new(pin: i32) -> io::Result<DrawerPin> {
+        match pin {
+            2 => Ok(DrawerPin::Pin2),
+            5 => Ok(DrawerPin::Pin5),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid drawer pin: {} (2 or 5)", pin),
+            )),
+        }
+    }
+}
+
+/// A printer operation, see the [Printer][crate::printer::Printer] method
+/// of the same name for details
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Command {
+    /// Bytes sent as they are
+    Raw(Vec<u8>),
+    /// `ESC @`
+    Init,
+    /// `ESC = 1`
+    Select,
+    /// `ESC ? LF 0`
+    Reset,
+    /// Text encoded with the current codec or code page, `\n` ends lines
+    Text(String),
+    /// Text with runs of CJK characters printed in Kanji mode
+    TextCjk(String),
+    CodePage(u8),
+    /// Encoding name of the codec, e.g. "windows-1252"
+    Codec(String),
+    /// International character set number (`ESC R`)
+    Charset(u8),
+    KanjiEncoding(KanjiEncoding),
+    KanjiMode {
+        double_width: bool,
+        double_height: bool,
+        underline: bool,
+    },
+    KanjiUnderline(u8),
+    KanjiQuadruple(bool),
+    /// Line spacing in dots, `None` for the default
+    LineSpace(Option<u8>),
+    TabStops(Vec<u8>),
+    LeftMargin(u16),
+    PrintAreaWidth(u16),
+    Position(u16),
+    RelativePosition(i16),
+    /// Newlines
+    Feed(usize),
+    FeedDots(u8),
+    FeedLines(u8),
+    ReverseFeedDots(u8),
+    ReverseFeedLines(u8),
+    FeedToCut,
+    Control(Control),
+    Align(Alignment),
+    Font(Font),
+    Bold(bool),
+    Underline(u8),
+    Reverse(bool),
+    UpsideDown(bool),
+    Rotate(bool),
+    DoubleStrike(bool),
+    Smoothing(bool),
+    CharSpacing(u8),
+    SecondColor(bool),
+    /// Width and height magnification (1-8)
+    Size {
+        width: u8,
+        height: u8,
+    },
+    Barcode {
+        code: String,
+        kind: BarcodeKind,
+        text: BarcodeText,
+        /// Font of the text, A or B
+        font: Font,
+        /// Module width (2-6)
+        width: u8,
+        /// Height in dots
+        height: u8,
+    },
+    QrCode {
+        code: String,
+        version: u8,
+        level: QrLevel,
+        /// Module size in dots
+        size: u8,
+    },
+    /// Image with the graphics command preferred by the profile
+    Image(Image),
+    BitImage {
+        image: Image,
+        density: BitImageDensity,
+    },
+    Raster {
+        image: Image,
+        mode: RasterMode,
+    },
+    CashDraw(DrawerPin),
+    DrawerPulse {
+        pin: DrawerPin,
+        on_ms: u16,
+        off_ms: u16,
+    },
+    DrawerPulseRealtime {
+        pin: DrawerPin,
+        on_ms: u16,
+    },
+    Beep {
+        times: u8,
+        duration: Duration,
+    },
+    Buzzer {
+        pattern: BuzzerPattern,
+        times: u8,
+        pause: Duration,
+    },
+    ExternalBuzzer {
+        pattern: BuzzerPattern,
+        times: u8,
+        on: Duration,
+        off: Duration,
+    },
+    DefineMacro(Vec<Command>),
+    RunMacro {
+        times: u8,
+        wait: Duration,
+        mode: MacroMode,
+    },
+    Cut {
+        mode: CutMode,
+        feed: Option<u8>,
+    },
+    /// Enter page mode (`ESC L`)
+    PageMode,
+    /// Return to standard mode without printing the page (`ESC S`)
+    StandardMode,
+    PageArea(Area),
+    PageDirection(Direction),
+    /// Position in the page area (`ESC $`, `GS $`)
+    PagePosition {
+        x: u16,
+        y: u16,
+    },
+    /// Print the page and stay in page mode (`ESC FF`)
+    PrintPage,
+    /// Discard the page data (`CAN`)
+    CancelPage,
+}
+
+impl Command {
+    /// Line of text, `content` followed by a newline
+    pub fn line(content: &str) -> Command {
+        Command::Text(format!("{}{}", content, consts::EOL))
+    }
+
+    /// Bold and underline of a style name ("B", "U", "U2", "BU", "BU2" or
+    /// "NORMAL")
+    pub fn style(kind: &str) -> [Command; 2] {
+        let (bold, underline) = match kind.to_uppercase().as_ref() {
+            "B" => (true, 0),
+            "U" => (false, 1),
+            "U2" => (false, 2),
+            "BU" => (true, 1),
+            "BU2" => (true, 2),
+            // "NORMAL" | _ =>
+            _ => (false, 0),
+        };
+        [Command::Bold(bold), Command::Underline(underline)]
+    }
+
+    /// International character set by country name, e.g. "GERMANY"
+    pub fn charset(charset: &str) -> io::Result<Command> {
+        let charset_upper = charset.to_uppercase().replace('-', "_");
+        let charset_value = match charset_upper.as_ref() {
+            "USA" => 0,
+            "FRANCE" => 1,
+            "GERMANY" => 2,
+            "UK" => 3,
+            "DENMARK1" => 4,
+            "SWEDEN" => 5,
+            "ITALY" => 6,
+            "SPAIN1" => 7,
+            "JAPAN" => 8,
+            "NORWAY" => 9,
+            "DENMARK2" => 10,
+            "SPAIN2" => 11,
+            "LATIN_AMERICA" => 12,
+            "KOREA" => 13,
+            "SLOVENIA" | "CROATIA" => 14,
+            "CHINA" => 15,
+            "VIETNAM" => 16,
+            "ARABIA" => 17,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid international character set: {}", charset),
+                ))
+            }
+        };
+        Ok(Command::Charset(charset_value))
+    }
+
+    /// Barcode of `kind` (EAN13 if unknown) with the text at `position`,
+    /// see [BarcodeText::from_name]. Widths outside 2-6 and heights outside
+    /// 1-255 fall back to 3 and 100.
+    pub fn barcode(
+        code: &str,
+        kind: &str,
+        position: &str,
+        font: &str,
+        width: usize,
+        height: usize,
+    ) -> Command {
+        Command::Barcode {
+            code: code.to_string(),
+            kind: BarcodeKind::parse(kind).unwrap_or(BarcodeKind::Ean13),
+            text: BarcodeText::from_name(position),
+            font: match font.to_uppercase().as_ref() {
+                "B" => Font::B,
+                // "A" | _ =>
+                _ => Font::A,
+            },
+            width: if (2..=6).contains(&width) {
+                width as u8
+            } else {
+                3
+            },
+            height: if (1..=255).contains(&height) {
+                height as u8
+            } else {
+                100
+            },
+        }
+    }
+}
+
+/// A list of commands, e.g. a receipt
+///
+/// With the `serde` feature documents serialize as a list of commands, and
+/// also deserialize from the printer method calls of the `call` module.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Document {
+    pub commands: Vec<Command>,
+}
+
+impl Document {
+    pub fn new() -> Document {
+        Document::default()
+    }
+
+    pub fn push(&mut self, command: Command) -> &mut Self {
+        self.commands.push(command);
+        self
+    }
+
+    pub fn iter(&self) -> slice::Iter<Command> {
+        self.commands.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// ESC/POS bytes of the document for the default profile, see
+    /// [Encoder::encode_document] for other settings
+    pub fn encode(&self) -> io::Result<Vec<u8>> {
+        Encoder::default().encode_document(self)
+    }
+}
+
+impl From<Vec<Command>> for Document {
+    fn from(commands: Vec<Command>) -> Document {
+        Document { commands }
+    }
+}
+
+impl FromIterator<Command> for Document {
+    fn from_iter<I: IntoIterator<Item = Command>>(iter: I) -> Document {
+        Document {
+            commands: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<Command> for Document {
+    fn extend<I: IntoIterator<Item = Command>>(&mut self, iter: I) {
+        self.commands.extend(iter)
+    }
+}
+
+impl IntoIterator for Document {
+    type Item = Command;
+    type IntoIter = vec::IntoIter<Command>;
+
+    fn into_iter(self) -> vec::IntoIter<Command> {
+        self.commands.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Document {
+    type Item = &'a Command;
+    type IntoIter = slice::Iter<'a, Command>;
+
+    fn into_iter(self) -> slice::Iter<'a, Command> {
+        self.commands.iter()
+    }
+}
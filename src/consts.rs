@@ -27,6 +27,24 @@ pub const CTL_VT: &[u8] = b"\x0b"; // Vertical tab
 pub const LS_DEFAULT: &[u8] = b"\x1b\x32";
 pub const LS_SET: &[u8] = b"\x1b\x33";
 
+// .TAB_STOPS
+pub const TAB_STOPS: &[u8] = b"\x1b\x44"; // Horizontal tab positions [n1 ... nk NUL]
+
+// .PAGE_MODE
+pub const PAGE_MODE: &[u8] = b"\x1b\x4c"; // Select page mode
+pub const STANDARD_MODE: &[u8] = b"\x1b\x53"; // Select standard mode
+pub const PAGE_AREA: &[u8] = b"\x1b\x57"; // Print area [xL xH yL yH dxL dxH dyL dyH]
+pub const PAGE_DIRECTION: &[u8] = b"\x1b\x54"; // Print direction [n]
+pub const PAGE_VERTICAL: &[u8] = b"\x1d\x24"; // Absolute vertical position [nL nH]
+pub const PAGE_PRINT: &[u8] = b"\x1b\x0c"; // Print the page, stay in page mode
+pub const PAGE_CANCEL: &[u8] = b"\x18"; // Discard the page data
+
+// .PRINT_POSITION
+pub const POS_LEFT_MARGIN: &[u8] = b"\x1d\x4c"; // Left margin [nL nH]
+pub const POS_AREA_WIDTH: &[u8] = b"\x1d\x57"; // Printing area width [nL nH]
+pub const POS_ABSOLUTE: &[u8] = b"\x1b\x24"; // Absolute horizontal position [nL nH]
+pub const POS_RELATIVE: &[u8] = b"\x1b\x5c"; // Relative horizontal position [nL nH]
+
 /**
  * [`HARDWARE` Printer hardware]
  */
@@ -35,12 +53,52 @@ pub const HW_INIT: &[u8] = b"\x1b\x40"; // Clear data in buffer and reset modes
 pub const HW_SELECT: &[u8] = b"\x1b\x3d\x01"; // Printer select
 pub const HW_RESET: &[u8] = b"\x1b\x3f\x0a\x00"; // Reset printer hardware
 
+/**
+ * [`PRINTER_INFO` Printer information]
+ */
+// .PRINTER_INFO
+pub const INFO_MODEL_ID: &[u8] = b"\x1d\x49\x01"; // Transmit printer model ID
+pub const INFO_TYPE_ID: &[u8] = b"\x1d\x49\x02"; // Transmit printer type ID
+pub const INFO_ROM_VERSION: &[u8] = b"\x1d\x49\x03"; // Transmit ROM version ID
+pub const INFO_FIRMWARE: &[u8] = b"\x1d\x49\x41"; // Transmit firmware version
+pub const INFO_MANUFACTURER: &[u8] = b"\x1d\x49\x42"; // Transmit maker name
+pub const INFO_MODEL_NAME: &[u8] = b"\x1d\x49\x43"; // Transmit model name
+pub const INFO_SERIAL: &[u8] = b"\x1d\x49\x44"; // Transmit serial number
+pub const INFO_FONT_LANGUAGE: &[u8] = b"\x1d\x49\x45"; // Transmit font of language
+pub const INFO_CUSTOM_VALUE: &[u8] = b"\x1d\x28\x45\x02\x00\x06"; // Transmit customized setting value [a]
+
+pub const INFO_BLOCK_HEADER: u8 = 0x5f; // "_" header of GS I text blocks
+pub const INFO_CUSTOM_HEADER: u8 = 0x37; // Header of GS ( E responses
+pub const INFO_SEPARATOR: u8 = 0x1f; // Separator inside GS ( E responses
+pub const INFO_TERMINATOR: u8 = 0x00; // NUL terminating a response block
+
 /**
  * [`CASH_DRAWER` Cash Drawer]
  */
 // .CASH_DRAWER
-pub const CD_KICK_2: &[u8] = b"\x1b\x70\x00"; // Sends a pulse to pin 2 []
-pub const CD_KICK_5: &[u8] = b"\x1b\x70\x01"; // Sends a pulse to pin 5 []
+pub const CD_KICK_2: &[u8] = b"\x1b\x70\x00\x19\xfa"; // Sends a 50 ms pulse to pin 2, then waits 500 ms
+pub const CD_KICK_5: &[u8] = b"\x1b\x70\x01\x19\xfa"; // Sends a 50 ms pulse to pin 5, then waits 500 ms
+pub const CD_PULSE: &[u8] = b"\x1b\x70"; // Sends a pulse [m t1 t2]
+pub const CD_REALTIME_PULSE: &[u8] = b"\x10\x14\x01"; // Sends a pulse in real time [m t]
+
+// .BUZZER
+pub const BUZZER_BEEP: &[u8] = b"\x1b\x42"; // Beep [n times, t x 50 ms]
+pub const BUZZER_PATTERN: &[u8] = b"\x1b\x28\x41\x04\x00\x30"; // Beep pattern [n c t]
+pub const BUZZER_EXTERNAL: &[u8] = b"\x1b\x28\x41\x05\x00\x61"; // External buzzer [n c t1 t2]
+
+// .MACRO
+pub const MACRO_DEFINE: &[u8] = b"\x1d\x3a"; // Start or end a macro definition
+pub const MACRO_RUN: &[u8] = b"\x1d\x5e"; // Execute the macro [r t m]
+
+// .REALTIME_STATUS
+pub const RT_STATUS_PRINTER: &[u8] = b"\x10\x04\x01"; // Printer status, drawer pin 3 in bit 2
+pub const RT_STATUS_OFFLINE: &[u8] = b"\x10\x04\x02"; // Offline cause status
+pub const RT_STATUS_ERROR: &[u8] = b"\x10\x04\x03"; // Error cause status
+pub const RT_STATUS_PAPER: &[u8] = b"\x10\x04\x04"; // Roll paper sensor status
+
+// .RESPONSE
+pub const PROCESS_ID_REQUEST: &[u8] = b"\x1d\x28\x48\x06\x00\x30\x30"; // Request process ID response [d1 d2 d3 d4]
+pub const PROCESS_ID_HEADER: &[u8] = b"\x37\x22"; // Header of process ID responses [d1 d2 d3 d4 NUL]
 
 /**
  * [`PAPER` Paper]
@@ -48,8 +106,17 @@ pub const CD_KICK_5: &[u8] = b"\x1b\x70\x01"; // Sends a pulse to pin 5 []
 // .PAPER
 pub const PAPER_FULL_CUT: &[u8] = b"\x1d\x56\x00"; // Full cut paper
 pub const PAPER_PART_CUT: &[u8] = b"\x1d\x56\x01"; // Partial cut paper
-pub const PAPER_CUT_A: &[u8] = b"\x1d\x56\x41"; // Partial cut paper
-pub const PAPER_CUT_B: &[u8] = b"\x1d\x56\x42"; // Partial cut paper
+pub const PAPER_CUT_A: &[u8] = b"\x1d\x56\x41"; // Feed to cutting position + [n] and full cut
+pub const PAPER_CUT_B: &[u8] = b"\x1d\x56\x42"; // Feed to cutting position + [n] and partial cut
+pub const PAPER_RESERVE_FULL_CUT: &[u8] = b"\x1d\x56\x61"; // Full cut when printing reaches cutting position + [n]
+pub const PAPER_RESERVE_PART_CUT: &[u8] = b"\x1d\x56\x62"; // Partial cut when printing reaches cutting position + [n]
+pub const PAPER_RETURN_FULL_CUT: &[u8] = b"\x1d\x56\x67"; // Feed to cutting position + [n], full cut and feed back to print start
+pub const PAPER_RETURN_PART_CUT: &[u8] = b"\x1d\x56\x68"; // Feed to cutting position + [n], partial cut and feed back to print start
+pub const PAPER_FEED_DOTS: &[u8] = b"\x1b\x4a"; // Print and feed [n] motion units
+pub const PAPER_FEED_LINES: &[u8] = b"\x1b\x64"; // Print and feed [n] lines
+pub const PAPER_REVERSE_DOTS: &[u8] = b"\x1b\x4b"; // Print and reverse feed [n] motion units
+pub const PAPER_REVERSE_LINES: &[u8] = b"\x1b\x65"; // Print and reverse feed [n] lines
+pub const PAPER_FEED_TO_CUT: &[u8] = b"\x1c\x28\x4c\x02\x00\x42\x30"; // Feed to the cutting position
 
 /**
  * [`TEXT_FORMAT` Text format]
@@ -58,6 +125,7 @@ pub const PAPER_CUT_B: &[u8] = b"\x1d\x56\x42"; // Partial cut paper
 pub const TXT_NORMAL: &[u8] = b"\x1b\x21\x00"; // Normal text
 pub const TXT_2HEIGHT: &[u8] = b"\x1b\x21\x10"; // Double height text
 pub const TXT_2WIDTH: &[u8] = b"\x1b\x21\x20"; // Double width text
+pub const TXT_SIZE: &[u8] = b"\x1d\x21"; // Character size [(width - 1) << 4 | (height - 1)]
 
 pub const TXT_UNDERL_OFF: &[u8] = b"\x1b\x2d\x00"; // Underline font OFF
 pub const TXT_UNDERL_ON: &[u8] = b"\x1b\x2d\x01"; // Underline font 1-dot ON
@@ -65,6 +133,14 @@ pub const TXT_UNDERL2_ON: &[u8] = b"\x1b\x2d\x02"; // Underline font 2-dot ON
 pub const TXT_BOLD_OFF: &[u8] = b"\x1b\x45\x00"; // Bold font OFF
 pub const TXT_BOLD_ON: &[u8] = b"\x1b\x45\x01"; // Bold font ON
 
+pub const TXT_REVERSE: &[u8] = b"\x1d\x42"; // White/black reverse print [0-1]
+pub const TXT_UPSIDE_DOWN: &[u8] = b"\x1b\x7b"; // Upside-down print [0-1]
+pub const TXT_ROTATE: &[u8] = b"\x1b\x56"; // 90 degree clockwise rotation [0-1]
+pub const TXT_DOUBLE_STRIKE: &[u8] = b"\x1b\x47"; // Double-strike [0-1]
+pub const TXT_SMOOTHING: &[u8] = b"\x1d\x62"; // Smoothing [0-1]
+pub const TXT_SPACING: &[u8] = b"\x1b\x20"; // Right-side character spacing [n]
+pub const TXT_COLOR: &[u8] = b"\x1b\x72"; // Print color [0-1]
+
 pub const TXT_FONT_A: &[u8] = b"\x1b\x4d\x00"; // Font type A
 pub const TXT_FONT_B: &[u8] = b"\x1b\x4d\x01"; // Font type B
 pub const TXT_FONT_C: &[u8] = b"\x1b\x4d\x02"; // Font type C
@@ -73,6 +149,21 @@ pub const TXT_ALIGN_LT: &[u8] = b"\x1b\x61\x00"; // Left justification
 pub const TXT_ALIGN_CT: &[u8] = b"\x1b\x61\x01"; // Centering
 pub const TXT_ALIGN_RT: &[u8] = b"\x1b\x61\x02"; // Right justification
 
+pub const CODE_PAGE: &[u8] = b"\x1b\x74"; // Select character code table [n]
+pub const CHARSET: &[u8] = b"\x1b\x52"; // Select international character set [n]
+
+/**
+ * [`KANJI` Kanji / multi-byte characters]
+ */
+// .KANJI
+pub const KANJI_ON: &[u8] = b"\x1c\x26"; // Select Kanji character mode
+pub const KANJI_OFF: &[u8] = b"\x1c\x2e"; // Cancel Kanji character mode
+pub const KANJI_CODE_JIS: &[u8] = b"\x1c\x43\x00"; // Kanji code system JIS
+pub const KANJI_CODE_SJIS: &[u8] = b"\x1c\x43\x01"; // Kanji code system Shift JIS
+pub const KANJI_MODE: &[u8] = b"\x1c\x21"; // Select print mode for Kanji characters [n]
+pub const KANJI_UNDERLINE: &[u8] = b"\x1c\x2d"; // Kanji underline mode [0-2]
+pub const KANJI_QUADRUPLE: &[u8] = b"\x1c\x57"; // Kanji quadruple-size mode [0-1]
+
 /**
  * [`BARCODE_FORMAT` Barcode format]
  */
@@ -87,6 +178,8 @@ pub const BARCODE_FONT_B: &[u8] = b"\x1d\x66\x01"; // Font type B for HRI barcod
 
 pub const BARCODE_HEIGHT: &[u8] = b"\x1d\x68\x64"; // Barcode Height [1-255]
 pub const BARCODE_WIDTH: &[u8] = b"\x1d\x77\x03"; // Barcode Width  [2-6]
+pub const BARCODE_SET_HEIGHT: &[u8] = b"\x1d\x68"; // Set barcode height [1-255]
+pub const BARCODE_SET_WIDTH: &[u8] = b"\x1d\x77"; // Set barcode width [2-6]
 
 pub const BARCODE_UPC_A: &[u8] = b"\x1d\x6b\x00"; // Barcode type UPC-A
 pub const BARCODE_UPC_E: &[u8] = b"\x1d\x6b\x01"; // Barcode type UPC-E
@@ -0,0 +1,793 @@
+//! Decode ESC/POS byte streams into commands
+//!
+//! The [Decoder] is the reverse of the [Encoder][crate::encoder::Encoder],
+//! for reading output captured with [File][crate::device::File] or from
+//! the network. Commands it doesn't know are kept as [Item::Unknown] and
+//! decoding resumes after them. Printing a `GS ( k` QR code decodes into a
+//! [Command::QrCode] of the data, size and error correction set before it.
+//!
+//! ```rust
+//! use escposify::command::Command;
+//! use escposify::decoder::{self, Item};
+//!
+//! let entries = decoder::decode(b"\x1b\x45\x01Hi\n\x1d\x56\x00");
+//! assert_eq!(entries[0].item, Item::Command(Command::Bold(true)));
+//! assert_eq!(entries[1].item, Item::Command(Command::Text("Hi\n".to_string())));
+//! print!("{}", decoder::listing(&entries));
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+use encoding::all::{BIG5_2003, EUC_JP, GBK, UTF_8, WINDOWS_31J, WINDOWS_949};
+use encoding::types::{DecoderTrap, Encoding, EncodingRef};
+
+use crate::codepage::CodePages;
+use crate::command::{
+    BarcodeKind, BarcodeText, BitImageDensity, BuzzerPattern, Command, Control, CutMode, Document,
+    DrawerPin, KanjiEncoding, MacroMode, QrLevel, RasterMode,
+};
+use crate::consts;
+use crate::img::Image;
+use crate::page::{Area, Direction};
+use crate::profile::Profile;
+use crate::style::{Alignment, Font};
+
+const ESC: u8 = 0x1b;
+const GS: u8 = 0x1d;
+const FS: u8 = 0x1c;
+const DLE: u8 = 0x10;
+
+/// What a decoded entry is
+#[derive(Clone, Debug, PartialEq)]
+pub enum Item {
+    Command(Command),
+    /// A known command without [Command] equivalent, e.g. a status request
+    Other {
+        name: String,
+        params: Vec<u8>,
+    },
+    /// Bytes that are not a known command, or a truncated one
+    Unknown(Vec<u8>),
+}
+
+/// A decoded command and the bytes it was decoded from
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    /// Position of the first byte in the stream
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+    pub item: Item,
+}
+
+fn other(name: &str, params: &[u8]) -> Item {
+    Item::Other {
+        name: name.to_string(),
+        params: params.to_vec(),
+    }
+}
+
+/// `n` of switches that accept both `0`/`1` and `'0'`/`'1'`
+fn flag(n: u8) -> bool {
+    n & 0x01 != 0
+}
+
+fn u16le(bytes: &[u8]) -> u16 {
+    u16::from_le_bytes([bytes[0], bytes[1]])
+}
+
+fn drawer_pin(m: u8) -> DrawerPin {
+    if flag(m) {
+        DrawerPin::Pin5
+    } else {
+        DrawerPin::Pin2
+    }
+}
+
+fn buzzer_pattern(n: u8) -> Option<BuzzerPattern> {
+    match n {
+        1 => Some(BuzzerPattern::A),
+        2 => Some(BuzzerPattern::B),
+        3 => Some(BuzzerPattern::C),
+        4 => Some(BuzzerPattern::D),
+        5 => Some(BuzzerPattern::E),
+        _ => None,
+    }
+}
+
+/// Name of a `GS ( k` function, `cn` selects the symbol
+fn code2d_function(cn: u8, fn_: u8) -> String {
+    let symbol = match cn {
+        48 => "PDF417",
+        49 => "QR code",
+        50 => "MaxiCode",
+        51 => "GS1 DataBar",
+        52 => "Composite symbol",
+        53 => "Aztec code",
+        54 => "DataMatrix",
+        _ => "2D code",
+    };
+    let function = match fn_ {
+        65 => "select model",
+        67 => "set module size",
+        69 => "set error correction",
+        80 => "store data",
+        81 => "print",
+        82 => "transmit size",
+        _ => "function",
+    };
+    format!("{}: {}", symbol, function)
+}
+
+/// Name of a `GS ( L` / `GS 8 L` graphics function
+fn graphics_function(fn_: u8) -> &'static str {
+    match fn_ {
+        48 | 0 => "Graphics: transmit NV capacity",
+        49 | 1 => "Graphics: set reference dot density",
+        50 | 2 => "Graphics: print buffered graphics",
+        51 | 3 => "Graphics: transmit buffer capacity",
+        64 => "Graphics: transmit NV key codes",
+        65 => "Graphics: delete all NV graphics",
+        66 => "Graphics: delete NV graphics",
+        67 => "Graphics: define NV raster graphics",
+        69 => "Graphics: print NV graphics",
+        83 => "Graphics: define NV column graphics",
+        112 => "Graphics: store raster graphics",
+        113 => "Graphics: store column graphics",
+        _ => "Graphics function",
+    }
+}
+
+/// Decoding state that changes the meaning of later bytes
+pub struct Decoder {
+    codec: EncodingRef,
+    code_pages: CodePages,
+    code_page: Option<u8>,
+    kanji: KanjiEncoding,
+    /// Barcode width, height, font and text position of `GS k`
+    barcode: (u8, u8, Font, BarcodeText),
+    /// Module size, error correction and data of `GS ( k` QR codes
+    qr: (u8, QrLevel, Vec<u8>),
+}
+
+impl Decoder {
+    /// Text is decoded with `codec` (UTF-8 by default) until a code page of
+    /// the default profile is selected
+    pub fn new(codec: Option<EncodingRef>) -> Decoder {
+        let mut decoder = Decoder {
+            codec: codec.unwrap_or(UTF_8 as EncodingRef),
+            code_pages: CodePages::default(),
+            code_page: None,
+            kanji: KanjiEncoding::Gb2312,
+            barcode: (3, 162, Font::A, BarcodeText::Off),
+            qr: (3, QrLevel::L, vec![]),
+        };
+        decoder.set_profile(&Profile::default());
+        decoder
+    }
+
+    /// Decode text with the code pages of `profile`
+    pub fn set_profile(&mut self, profile: &Profile) {
+        self.code_pages = CodePages::new(
+            profile
+                .code_pages
+                .iter()
+                .map(|(number, label)| (*number, label.as_str())),
+        );
+    }
+
+    /// Decode a whole stream
+    pub fn decode(&mut self, data: &[u8]) -> Vec<Entry> {
+        let mut entries = vec![];
+        let mut pos = 0;
+        while pos < data.len() {
+            let (len, item) = self.next(&data[pos..]);
+            entries.push(Entry {
+                offset: pos,
+                bytes: data[pos..pos + len].to_vec(),
+                item,
+            });
+            pos += len;
+        }
+        entries
+    }
+
+    /// Length and item of the command at the start of `data`
+    fn next(&mut self, data: &[u8]) -> (usize, Item) {
+        let decoded = match data[0] {
+            ESC => self.esc(data),
+            GS => self.gs(data),
+            FS => self.fs(data),
+            DLE => self.dle(data),
+            0x18 => Some((1, Item::Command(Command::CancelPage))),
+            0x09 => Some((1, Item::Command(Command::Control(Control::Ht)))),
+            0x0b => Some((1, Item::Command(Command::Control(Control::Vt)))),
+            0x0c => Some((1, Item::Command(Command::Control(Control::Ff)))),
+            0x0d => Some((1, Item::Command(Command::Control(Control::Cr)))),
+            b if b >= 0x20 || b == b'\n' => {
+                let len = data
+                    .iter()
+                    .position(|&b| b < 0x20 && b != b'\n')
+                    .unwrap_or(data.len());
+                let text = self.decode_text(&data[..len]);
+                Some((len, Item::Command(Command::Text(text))))
+            }
+            _ => Some((1, Item::Unknown(data[..1].to_vec()))),
+        };
+        // Truncated commands swallow the rest of the stream
+        decoded.unwrap_or_else(|| (data.len(), Item::Unknown(data.to_vec())))
+    }
+
+    fn decode_text(&self, bytes: &[u8]) -> String {
+        match self.code_page.and_then(|n| self.code_pages.get(n)) {
+            Some(page) => page.decode(bytes),
+            None => self
+                .codec
+                .decode(bytes, DecoderTrap::Replace)
+                .unwrap_or_default(),
+        }
+    }
+
+    fn decode_kanji(&self, bytes: &[u8]) -> String {
+        let codec = match self.kanji {
+            KanjiEncoding::Jis => {
+                // JIS is EUC-JP without the high bits
+                let euc: Vec<u8> = bytes.iter().map(|b| b | 0x80).collect();
+                return EUC_JP
+                    .decode(&euc, DecoderTrap::Replace)
+                    .unwrap_or_default();
+            }
+            KanjiEncoding::ShiftJis => WINDOWS_31J as EncodingRef,
+            KanjiEncoding::Gb2312 => GBK,
+            KanjiEncoding::Big5 => BIG5_2003,
+            KanjiEncoding::Ksc5601 => WINDOWS_949,
+        };
+        codec
+            .decode(bytes, DecoderTrap::Replace)
+            .unwrap_or_default()
+    }
+
+    /// `prefix pL pH` functions, `len` bytes before the parameter length
+    fn function(data: &[u8]) -> Option<(usize, &[u8])> {
+        let p = u16le(data.get(3..5)?) as usize;
+        Some((5 + p, data.get(5..5 + p)?))
+    }
+
+    fn esc(&mut self, data: &[u8]) -> Option<(usize, Item)> {
+        let cmd = |len: usize, command: Command| Some((len, Item::Command(command)));
+        let n = data.get(2).copied();
+        let item = match *data.get(1)? {
+            b'@' => {
+                self.code_page = None;
+                self.qr = (3, QrLevel::L, vec![]);
+                cmd(2, Command::Init)
+            }
+            b'=' if n? == 1 => cmd(3, Command::Select),
+            b'=' => Some((3, other("Select peripheral device", &data[2..3]))),
+            b'?' if data.starts_with(consts::HW_RESET) => cmd(4, Command::Reset),
+            b'?' => Some((3, other("Cancel user-defined character", &data[2..3]))),
+            b'!' => Some((3, other("Select print mode", &data[2..3]))),
+            b'-' => cmd(3, Command::Underline(n? % 48)),
+            b'E' => cmd(3, Command::Bold(flag(n?))),
+            b'G' => cmd(3, Command::DoubleStrike(flag(n?))),
+            b'M' => match n? % 48 {
+                0 => cmd(3, Command::Font(Font::A)),
+                1 => cmd(3, Command::Font(Font::B)),
+                2 => cmd(3, Command::Font(Font::C)),
+                _ => Some((3, other("Select font", &data[2..3]))),
+            },
+            b'a' => match n? % 48 {
+                0 => cmd(3, Command::Align(Alignment::Left)),
+                1 => cmd(3, Command::Align(Alignment::Center)),
+                _ => cmd(3, Command::Align(Alignment::Right)),
+            },
+            b'{' => cmd(3, Command::UpsideDown(flag(n?))),
+            b'V' => cmd(3, Command::Rotate(flag(n?))),
+            b' ' => cmd(3, Command::CharSpacing(n?)),
+            b'r' => cmd(3, Command::SecondColor(flag(n?))),
+            b't' => {
+                self.code_page = Some(n?);
+                cmd(3, Command::CodePage(n?))
+            }
+            b'R' => cmd(3, Command::Charset(n?)),
+            b'2' => cmd(2, Command::LineSpace(None)),
+            b'3' => cmd(3, Command::LineSpace(Some(n?))),
+            b'D' => {
+                let end = data.iter().skip(2).position(|&b| b == 0)? + 2;
+                cmd(end + 1, Command::TabStops(data[2..end].to_vec()))
+            }
+            b'$' => cmd(4, Command::Position(u16le(data.get(2..4)?))),
+            b'\\' => cmd(4, Command::RelativePosition(u16le(data.get(2..4)?) as i16)),
+            b'J' => cmd(3, Command::FeedDots(n?)),
+            b'd' => cmd(3, Command::FeedLines(n?)),
+            b'K' => cmd(3, Command::ReverseFeedDots(n?)),
+            b'e' => cmd(3, Command::ReverseFeedLines(n?)),
+            b'p' => {
+                let bytes = data.get(..5)?;
+                if bytes == consts::CD_KICK_2 || bytes == consts::CD_KICK_5 {
+                    return cmd(5, Command::CashDraw(drawer_pin(bytes[2])));
+                }
+                cmd(
+                    5,
+                    Command::DrawerPulse {
+                        pin: drawer_pin(bytes[2]),
+                        on_ms: bytes[3] as u16 * 2,
+                        off_ms: bytes[4] as u16 * 2,
+                    },
+                )
+            }
+            b'B' => cmd(
+                4,
+                Command::Beep {
+                    times: n?,
+                    duration: Duration::from_millis(*data.get(3)? as u64 * 50),
+                },
+            ),
+            b'(' => {
+                let (len, params) = Decoder::function(data)?;
+                let unit = |t: u8| Duration::from_millis(t as u64 * 100);
+                let item = match (data[2], params) {
+                    (b'A', &[0x30, n, c, t]) if buzzer_pattern(n).is_some() => {
+                        Item::Command(Command::Buzzer {
+                            pattern: buzzer_pattern(n)?,
+                            times: c,
+                            pause: unit(t),
+                        })
+                    }
+                    (b'A', &[0x61, n, c, t1, t2]) if buzzer_pattern(n).is_some() => {
+                        Item::Command(Command::ExternalBuzzer {
+                            pattern: buzzer_pattern(n)?,
+                            times: c,
+                            on: unit(t1),
+                            off: unit(t2),
+                        })
+                    }
+                    (b'A', _) => other("Buzzer", params),
+                    (x, _) => other(&format!("ESC ( {}", x as char), params),
+                };
+                Some((len, item))
+            }
+            b'*' => {
+                let m = n?;
+                let (density, dots) = match m {
+                    0 => (BitImageDensity::S8, 8),
+                    1 => (BitImageDensity::D8, 8),
+                    32 => (BitImageDensity::S24, 24),
+                    33 => (BitImageDensity::D24, 24),
+                    _ => return Some((3, other("Select bit-image mode", &data[2..3]))),
+                };
+                let width = u16le(data.get(3..5)?) as u32;
+                let len = 5 + (width * dots / 8) as usize;
+                let image = Image::from_columns(width, dots, data.get(5..len)?);
+                cmd(len, Command::BitImage { image, density })
+            }
+            b'L' => cmd(2, Command::PageMode),
+            b'S' => cmd(2, Command::StandardMode),
+            b'W' => {
+                let p = data.get(2..10)?;
+                cmd(
+                    10,
+                    Command::PageArea(Area::new(
+                        u16le(&p[0..]),
+                        u16le(&p[2..]),
+                        u16le(&p[4..]),
+                        u16le(&p[6..]),
+                    )),
+                )
+            }
+            b'T' => {
+                let direction = match n? % 48 {
+                    0 => Direction::LeftToRight,
+                    1 => Direction::BottomToTop,
+                    2 => Direction::RightToLeft,
+                    _ => Direction::TopToBottom,
+                };
+                cmd(3, Command::PageDirection(direction))
+            }
+            0x0c => cmd(2, Command::PrintPage),
+            b'Z' => {
+                let p = data.get(2..7)?;
+                let len = 7 + u16le(&p[3..]) as usize;
+                let code = String::from_utf8_lossy(data.get(7..len)?).into_owned();
+                let level = match p[1] {
+                    b'M' => QrLevel::M,
+                    b'Q' => QrLevel::Q,
+                    b'H' => QrLevel::H,
+                    _ => QrLevel::L,
+                };
+                cmd(
+                    len,
+                    Command::QrCode {
+                        code,
+                        version: p[0],
+                        level,
+                        size: p[2],
+                    },
+                )
+            }
+            b'c' => Some((4, other("Select paper sensors", data.get(2..4)?))),
+            b'U' => Some((3, other("Unidirectional printing", &data[2..3]))),
+            b'i' | b'm' => Some((2, other("Partial cut", &[]))),
+            _ => Some((2, Item::Unknown(data[..2].to_vec()))),
+        };
+        // Commands with a parameter need it to be present
+        item.filter(|(len, _)| *len <= data.len())
+    }
+
+    fn gs(&mut self, data: &[u8]) -> Option<(usize, Item)> {
+        let cmd = |len: usize, command: Command| Some((len, Item::Command(command)));
+        let n = data.get(2).copied();
+        let item = match *data.get(1)? {
+            b'!' => cmd(
+                3,
+                Command::Size {
+                    width: (n? >> 4) + 1,
+                    height: (n? & 0x07) + 1,
+                },
+            ),
+            b'B' => cmd(3, Command::Reverse(flag(n?))),
+            b'b' => cmd(3, Command::Smoothing(flag(n?))),
+            b'V' => {
+                let feed = data.get(3).copied();
+                let (mode, len) = match n? {
+                    0 | 48 => (CutMode::Full, 3),
+                    1 | 49 => (CutMode::Partial, 3),
+                    65 => (CutMode::Full, 4),
+                    66 => (CutMode::Partial, 4),
+                    97 => (CutMode::ReservedFull, 4),
+                    98 => (CutMode::ReservedPartial, 4),
+                    103 => (CutMode::ReturnFull, 4),
+                    104 => (CutMode::ReturnPartial, 4),
+                    _ => return Some((3, other("Cut", &data[2..3]))),
+                };
+                let feed = if len == 4 { Some(feed?) } else { None };
+                cmd(len, Command::Cut { mode, feed })
+            }
+            b'L' => cmd(4, Command::LeftMargin(u16le(data.get(2..4)?))),
+            b'W' => cmd(4, Command::PrintAreaWidth(u16le(data.get(2..4)?))),
+            b'$' => Some((4, other("Vertical position", data.get(2..4)?))),
+            b'w' => {
+                self.barcode.0 = n?;
+                Some((3, other("Barcode width", &data[2..3])))
+            }
+            b'h' => {
+                self.barcode.1 = n?;
+                Some((3, other("Barcode height", &data[2..3])))
+            }
+            b'f' => {
+                self.barcode.2 = if flag(n?) { Font::B } else { Font::A };
+                Some((3, other("Barcode text font", &data[2..3])))
+            }
+            b'H' => {
+                self.barcode.3 = match n? % 48 {
+                    0 => BarcodeText::Off,
+                    1 => BarcodeText::Above,
+                    2 => BarcodeText::Below,
+                    _ => BarcodeText::Both,
+                };
+                Some((3, other("Barcode text position", &data[2..3])))
+            }
+            b'k' => self.barcode(data),
+            b'v' if n? == b'0' => {
+                let p = data.get(3..8)?;
+                let (row, height) = (u16le(&p[1..]) as usize, u16le(&p[3..]) as u32);
+                let len = 8 + row * height as usize;
+                let image = Image::from_raster(row as u32 * 8, height, data.get(8..len)?);
+                let mode = match p[0] % 48 {
+                    0 => RasterMode::Normal,
+                    1 => RasterMode::DoubleWidth,
+                    2 => RasterMode::DoubleHeight,
+                    _ => RasterMode::Quadruple,
+                };
+                cmd(len, Command::Raster { image, mode })
+            }
+            b'(' => {
+                let (len, params) = Decoder::function(data)?;
+                if data[2] == b'k' {
+                    if let Some(command) = self.qr_function(params) {
+                        return cmd(len, command);
+                    }
+                }
+                let name = match (data[2], params) {
+                    (b'k', &[cn, fn_, ..]) => code2d_function(cn, fn_),
+                    (b'L', &[_, fn_, ..]) => graphics_function(fn_).to_string(),
+                    (b'A', _) => "Test print".to_string(),
+                    (b'E', _) => "User setup".to_string(),
+                    (b'H', _) => "Request response".to_string(),
+                    (x, _) => format!("GS ( {}", x as char),
+                };
+                Some((len, other(&name, params)))
+            }
+            b'8' if n? == b'L' => {
+                let p = u32::from_le_bytes(data.get(3..7)?.try_into().ok()?) as usize;
+                let params = data.get(7..7 + p)?;
+                let name = graphics_function(params.get(1).copied().unwrap_or(0));
+                Some((7 + p, other(name, params)))
+            }
+            b':' => self.define_macro(data),
+            b'^' => {
+                let p = data.get(2..5)?;
+                cmd(
+                    5,
+                    Command::RunMacro {
+                        times: p[0],
+                        wait: Duration::from_millis(p[1] as u64 * 100),
+                        mode: if flag(p[2]) {
+                            MacroMode::FeedButton
+                        } else {
+                            MacroMode::Continuous
+                        },
+                    },
+                )
+            }
+            b'Z' => Some((3, other("Select 2D code type", &data[2..3]))),
+            b'I' => Some((3, other("Transmit printer ID", &data[2..3]))),
+            b'a' => Some((3, other("Automatic status back", &data[2..3]))),
+            b'r' => Some((3, other("Transmit status", &data[2..3]))),
+            b'P' => Some((4, other("Set motion units", data.get(2..4)?))),
+            _ => Some((2, Item::Unknown(data[..2].to_vec()))),
+        };
+        item.filter(|(len, _)| *len <= data.len())
+    }
+
+    /// Track the `GS ( k` QR code settings, the print function is decoded
+    /// into a [Command::QrCode] of the stored data
+    fn qr_function(&mut self, params: &[u8]) -> Option<Command> {
+        let qr = &mut self.qr;
+        match *params {
+            [49, 67, n] => qr.0 = n,
+            [49, 69, n] => {
+                qr.1 = match n {
+                    48 => QrLevel::L,
+                    49 => QrLevel::M,
+                    50 => QrLevel::Q,
+                    _ => QrLevel::H,
+                }
+            }
+            [49, 80, 48, ref data @ ..] => qr.2 = data.to_vec(),
+            [49, 81, 48] => {
+                return Some(Command::QrCode {
+                    code: String::from_utf8_lossy(&qr.2).into_owned(),
+                    version: 0,
+                    level: qr.1,
+                    size: qr.0,
+                })
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// `GS k`, NUL terminated (`m` 0-6) or with a length (`m` 65-73)
+    fn barcode(&mut self, data: &[u8]) -> Option<(usize, Item)> {
+        let m = *data.get(2)?;
+        let (kind, start, end) = match m {
+            0..=6 => {
+                let end = data.iter().skip(3).position(|&b| b == 0)? + 3;
+                (m, 3, end + 1)
+            }
+            65..=73 => {
+                let n = *data.get(3)? as usize;
+                (m - 65, 4, 4 + n)
+            }
+            _ => return Some((3, other("Barcode", &data[2..3]))),
+        };
+        let code_end = if m <= 6 { end - 1 } else { end };
+        let code = data.get(start..code_end)?;
+        let kind = match kind {
+            0 => BarcodeKind::UpcA,
+            1 => BarcodeKind::UpcE,
+            2 => BarcodeKind::Ean13,
+            3 => BarcodeKind::Ean8,
+            4 => BarcodeKind::Code39,
+            5 => BarcodeKind::Itf,
+            6 => BarcodeKind::Nw7,
+            7 => return Some((end, other("Barcode CODE93", code))),
+            _ => return Some((end, other("Barcode CODE128", code))),
+        };
+        let (width, height, font, text) = self.barcode;
+        let command = Command::Barcode {
+            code: String::from_utf8_lossy(code).into_owned(),
+            kind,
+            text,
+            font,
+            width,
+            height,
+        };
+        Some((end, Item::Command(command)))
+    }
+
+    /// `GS :` ... `GS :`, the commands in between are the macro
+    fn define_macro(&mut self, data: &[u8]) -> Option<(usize, Item)> {
+        let code_page = self.code_page;
+        let mut commands = vec![];
+        let mut pos = 2;
+        while !data[pos..].starts_with(consts::MACRO_DEFINE) {
+            if pos >= data.len() {
+                // Unterminated, decode the rest as usual
+                self.code_page = code_page;
+                return Some((2, other("Start macro definition", &[])));
+            }
+            let (len, item) = self.next(&data[pos..]);
+            if let Item::Command(command) = item {
+                commands.push(command);
+            }
+            pos += len;
+        }
+        self.code_page = code_page;
+        Some((pos + 2, Item::Command(Command::DefineMacro(commands))))
+    }
+
+    fn fs(&mut self, data: &[u8]) -> Option<(usize, Item)> {
+        let cmd = |len: usize, command: Command| Some((len, Item::Command(command)));
+        let n = data.get(2).copied();
+        let item = match *data.get(1)? {
+            b'&' => {
+                let end = data
+                    .windows(2)
+                    .skip(2)
+                    .position(|pair| pair == consts::KANJI_OFF)
+                    .map_or(data.len(), |i| i + 2);
+                let text = self.decode_kanji(&data[2..end]);
+                cmd((end + 2).min(data.len()), Command::TextCjk(text))
+            }
+            b'.' => Some((2, other("Cancel Kanji mode", &[]))),
+            b'C' => match n? % 48 {
+                0 => {
+                    self.kanji = KanjiEncoding::Jis;
+                    cmd(3, Command::KanjiEncoding(KanjiEncoding::Jis))
+                }
+                1 => {
+                    self.kanji = KanjiEncoding::ShiftJis;
+                    cmd(3, Command::KanjiEncoding(KanjiEncoding::ShiftJis))
+                }
+                _ => Some((3, other("Select Kanji code system", &data[2..3]))),
+            },
+            b'!' => cmd(
+                3,
+                Command::KanjiMode {
+                    double_width: n? & 0x04 != 0,
+                    double_height: n? & 0x08 != 0,
+                    underline: n? & 0x80 != 0,
+                },
+            ),
+            b'-' => cmd(3, Command::KanjiUnderline(n? % 48)),
+            b'W' => cmd(3, Command::KanjiQuadruple(flag(n?))),
+            b'(' => {
+                let (len, params) = Decoder::function(data)?;
+                let item = match (data[2], params) {
+                    (b'L', &[0x42, 0x30]) => Item::Command(Command::FeedToCut),
+                    (x, _) => other(&format!("FS ( {}", x as char), params),
+                };
+                Some((len, item))
+            }
+            b'p' => Some((4, other("Print NV bit image", data.get(2..4)?))),
+            _ => Some((2, Item::Unknown(data[..2].to_vec()))),
+        };
+        item.filter(|(len, _)| *len <= data.len())
+    }
+
+    fn dle(&mut self, data: &[u8]) -> Option<(usize, Item)> {
+        let item = match *data.get(1)? {
+            0x04 => Some((3, other("Real-time status", data.get(2..3)?))),
+            0x05 => Some((3, other("Real-time request", data.get(2..3)?))),
+            0x14 => match *data.get(2)? {
+                1 => {
+                    let p = data.get(3..5)?;
+                    let command = Command::DrawerPulseRealtime {
+                        pin: drawer_pin(p[0]),
+                        on_ms: p[1] as u16 * 100,
+                    };
+                    Some((5, Item::Command(command)))
+                }
+                2 => Some((5, other("Power off", data.get(3..5)?))),
+                8 => Some((10, other("Clear buffers", data.get(3..10)?))),
+                _ => Some((3, Item::Unknown(data[..3].to_vec()))),
+            },
+            _ => Some((1, Item::Unknown(data[..1].to_vec()))),
+        };
+        item.filter(|(len, _)| *len <= data.len())
+    }
+}
+
+impl Default for Decoder {
+    fn default() -> Decoder {
+        Decoder::new(None)
+    }
+}
+
+/// Decode `data` with the default settings
+pub fn decode(data: &[u8]) -> Vec<Entry> {
+    Decoder::default().decode(data)
+}
+
+/// The commands of `data` as a document, without unknown bytes
+pub fn document(data: &[u8]) -> Document {
+    decode(data)
+        .into_iter()
+        .filter_map(|entry| match entry.item {
+            Item::Command(command) => Some(command),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Short description of `command`, images by size instead of content
+fn describe(command: &Command) -> String {
+    match command {
+        Command::Image(image) => format!("Image {}x{}", image.width, image.height),
+        Command::BitImage { image, density } => {
+            format!("BitImage {}x{} {:?}", image.width, image.height, density)
+        }
+        Command::Raster { image, mode } => {
+            format!("Raster {}x{} {:?}", image.width, image.height, mode)
+        }
+        Command::DefineMacro(commands) => {
+            let commands: Vec<String> = commands.iter().map(describe).collect();
+            format!("DefineMacro [{}]", commands.join(", "))
+        }
+        command => format!("{:?}", command),
+    }
+}
+
+/// Mnemonic of the command bytes, e.g. `GS V`
+fn mnemonic(bytes: &[u8]) -> String {
+    let prefix = match bytes[0] {
+        ESC => "ESC",
+        GS => "GS",
+        FS => "FS",
+        DLE => "DLE",
+        _ => return String::new(),
+    };
+    match bytes.get(1) {
+        Some(&b) if b.is_ascii_graphic() => format!("{} {}", prefix, b as char),
+        Some(0x0c) => format!("{} FF", prefix),
+        Some(b' ') => format!("{} SP", prefix),
+        Some(b) => format!("{} {:#04x}", prefix, b),
+        None => prefix.to_string(),
+    }
+}
+
+impl fmt::Display for Entry {
+    /// One listing line: offset, mnemonic, leading bytes and description
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut hex: Vec<String> = self
+            .bytes
+            .iter()
+            .take(8)
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        if self.bytes.len() > 8 {
+            hex.push("..".to_string());
+        }
+        write!(
+            f,
+            "{:06x}  {:<8} {:<27} ",
+            self.offset,
+            mnemonic(&self.bytes),
+            hex.join(" ")
+        )?;
+        match self.item {
+            Item::Command(ref command) => write!(f, "{}", describe(command)),
+            Item::Other {
+                ref name,
+                ref params,
+            } if params.len() > 8 => write!(f, "{} ({} bytes)", name, params.len()),
+            Item::Other {
+                ref name,
+                ref params,
+            } => write!(f, "{} {:?}", name, params),
+            Item::Unknown(_) => write!(f, "Unknown"),
+        }
+    }
+}
+
+/// Human readable listing of `entries`, one line each
+pub fn listing(entries: &[Entry]) -> String {
+    let mut listing = String::new();
+    for entry in entries {
+        listing.push_str(&entry.to_string());
+        listing.push('\n');
+    }
+    listing
+}
@@ -2,6 +2,7 @@ use std::fs;
 use std::io;
 use std::net;
 use std::path;
+use std::time::Duration;
 
 use rusb::Direction;
 use rusb::TransferType;
@@ -13,6 +14,7 @@ pub struct Usb {
     _product_id: u16,
     connection: DeviceHandle<Context>,
     endpoint: u8,
+    in_endpoint: Option<u8>,
 }
 
 pub struct Serial {}
@@ -33,6 +35,17 @@ impl Network {
             stream,
         })
     }
+
+    /// Set the timeout for reading answers to status and info queries
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        self.stream.set_read_timeout(timeout)
+    }
+}
+
+impl io::Read for Network {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.read(buf)
+    }
 }
 
 impl io::Write for Network {
@@ -77,6 +90,12 @@ impl<W: io::Write> File<W> {
     }
 }
 
+impl<W: io::Read> io::Read for File<W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.fobj.read(buf)
+    }
+}
+
 impl<W: io::Write> io::Write for File<W> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.fobj.write(buf)
@@ -116,22 +135,25 @@ impl Usb {
                     .active_config_descriptor()
                     .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
 
-                let endpoint = config_descriptor
-                    .interfaces()
-                    .flat_map(|interface| interface.descriptors())
-                    .flat_map(|descriptor| descriptor.endpoint_descriptors())
-                    .find_map(|endpoint| {
-                        if let (TransferType::Bulk, Direction::Out) =
-                            (endpoint.transfer_type(), endpoint.direction())
-                        {
-                            Some(endpoint.number())
-                        } else {
-                            None
-                        }
-                    })
-                    .ok_or_else(|| {
-                        io::Error::new(io::ErrorKind::Other, "No suitable endpoint found")
-                    })?;
+                let bulk_endpoint = |direction: Direction| {
+                    config_descriptor
+                        .interfaces()
+                        .flat_map(|interface| interface.descriptors())
+                        .flat_map(|descriptor| descriptor.endpoint_descriptors())
+                        .find_map(|endpoint| {
+                            if endpoint.transfer_type() == TransferType::Bulk
+                                && endpoint.direction() == direction
+                            {
+                                Some(endpoint.address())
+                            } else {
+                                None
+                            }
+                        })
+                };
+                let endpoint = bulk_endpoint(Direction::Out).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::Other, "No suitable endpoint found")
+                })?;
+                let in_endpoint = bulk_endpoint(Direction::In);
 
                 match device.open() {
                     Ok(dvc) => {
@@ -155,6 +177,7 @@ impl Usb {
                                 _product_id: product_id,
                                 connection: dvc,
                                 endpoint,
+                                in_endpoint,
                             })
                             .map_err(|e| io::Error::new(io::ErrorKind::Other, e));
                     }
@@ -167,6 +190,24 @@ impl Usb {
     }
 }
 
+impl io::Read for Usb {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let endpoint = self.in_endpoint.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Unsupported, "No bulk IN endpoint found")
+        })?;
+        match self
+            .connection
+            .read_bulk(endpoint, buf, Duration::from_secs(5))
+        {
+            Ok(n) => Ok(n),
+            Err(rusb::Error::Timeout) => {
+                Err(io::Error::new(io::ErrorKind::TimedOut, "USB read timeout"))
+            }
+            Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
+        }
+    }
+}
+
 impl io::Write for Usb {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         match self
@@ -0,0 +1,663 @@
+//! Render ESC/POS output to an image of the printed receipt
+//!
+//! The [Emulator] prints the commands read by the [decoder][crate::decoder]
+//! on a paper roll of a given width in dots: text in bitmap fonts A and B
+//! with sizes and styles, alignment, line spacing, raster and bit images,
+//! barcodes and QR codes. Cuts are drawn as dashed lines. Page mode is
+//! printed like standard mode, status requests and unknown commands are
+//! ignored.
+//!
+//! ```rust
+//! use escposify::emulator::Emulator;
+//! use escposify::printer::Printer;
+//!
+//! let mut emulator = Emulator::new(384);
+//! {
+//!     let mut printer = Printer::new(&mut emulator, None, None);
+//!     printer
+//!         .chain_align("ct")
+//!         .unwrap()
+//!         .chain_println("Hello")
+//!         .unwrap()
+//!         .flush()
+//!         .unwrap();
+//! }
+//! let receipt = emulator.image();
+//! assert_eq!(receipt.width(), 384);
+//! ```
+
+use std::io;
+use std::mem;
+
+use embedded_graphics::geometry::{OriginDimensions, Point};
+use embedded_graphics::image::GetPixel;
+use embedded_graphics::mono_font::{iso_8859_1, MonoFont};
+use embedded_graphics::pixelcolor::BinaryColor;
+use image::{imageops, Rgb, RgbImage};
+use qrcode::{EcLevel, QrCode, Version};
+use unicode_width::UnicodeWidthChar;
+
+use crate::barcode;
+use crate::command::{
+    BarcodeKind, BarcodeText, BitImageDensity, Command, Control, Document, QrLevel, RasterMode,
+};
+use crate::decoder::{Decoder, Item};
+use crate::encoder::Encoder;
+use crate::img::Image;
+use crate::profile::Profile;
+use crate::style::{Alignment, Font};
+
+const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
+const WHITE: Rgb<u8> = Rgb([0xff, 0xff, 0xff]);
+/// Second color of two-color paper (`ESC r`)
+const RED: Rgb<u8> = Rgb([0xcc, 0, 0]);
+/// Default line spacing (`ESC 2`) in dots
+const LINE_SPACE: u32 = 30;
+/// Paper fed around the dashed line of a cut, in dots
+const CUT_GAP: u32 = 16;
+
+/// Monochrome dots of a glyph, image or barcode
+#[derive(Clone)]
+struct Dots {
+    width: u32,
+    height: u32,
+    dots: Vec<bool>,
+}
+
+impl Dots {
+    fn new(width: u32, height: u32) -> Dots {
+        Dots {
+            width,
+            height,
+            dots: vec![false; (width * height) as usize],
+        }
+    }
+
+    fn from_fn<F: Fn(u32, u32) -> bool>(width: u32, height: u32, dot: F) -> Dots {
+        let mut dots = Dots::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                if dot(x, y) {
+                    dots.set(x, y);
+                }
+            }
+        }
+        dots
+    }
+
+    fn from_image(image: &Image) -> Dots {
+        Dots::from_fn(image.width, image.height, |x, y| {
+            !image.is_blank_pixel(x, y)
+        })
+    }
+
+    fn get(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.dots[(y * self.width + x) as usize]
+    }
+
+    fn set(&mut self, x: u32, y: u32) {
+        if x < self.width && y < self.height {
+            self.dots[(y * self.width + x) as usize] = true;
+        }
+    }
+
+    /// Every dot `sx` dots wide and `sy` dots high
+    fn scale(&self, sx: u32, sy: u32) -> Dots {
+        Dots::from_fn(self.width * sx, self.height * sy, |x, y| {
+            self.get(x / sx, y / sy)
+        })
+    }
+
+    /// Rotated 90° clockwise
+    fn rotate(&self) -> Dots {
+        Dots::from_fn(self.height, self.width, |x, y| {
+            self.get(y, self.height - 1 - x)
+        })
+    }
+
+    /// Rotated 180°
+    fn flip(&self) -> Dots {
+        Dots::from_fn(self.width, self.height, |x, y| {
+            self.get(self.width - 1 - x, self.height - 1 - y)
+        })
+    }
+
+    /// Emphasized, every dot printed twice one dot apart
+    fn bold(&self) -> Dots {
+        Dots::from_fn(self.width, self.height, |x, y| {
+            self.get(x, y) || (x > 0 && self.get(x - 1, y))
+        })
+    }
+
+    /// Set the dots of `other` with its top left corner at `x`, `y`
+    fn draw(&mut self, other: &Dots, x: u32, y: u32) {
+        for oy in 0..other.height {
+            for ox in 0..other.width {
+                if other.get(ox, oy) {
+                    self.set(x + ox, y + oy);
+                }
+            }
+        }
+    }
+
+    /// `parts` below each other, centered
+    fn stack(parts: &[Dots]) -> Dots {
+        let width = parts.iter().map(|part| part.width).max().unwrap_or(0);
+        let mut dots = Dots::new(width, parts.iter().map(|part| part.height).sum());
+        let mut y = 0;
+        for part in parts {
+            dots.draw(part, (width - part.width) / 2, y);
+            y += part.height;
+        }
+        dots
+    }
+}
+
+/// Glyph of `c` centered in a cell of `width` x `height` dots
+fn glyph(font: &MonoFont, c: char, width: u32, height: u32) -> Dots {
+    let size = font.character_size;
+    let columns = font.image.size().width / size.width;
+    let index = font.glyph_mapping.index(c) as u32;
+    let left = index % columns * size.width;
+    let top = index / columns * size.height;
+    let dx = width.saturating_sub(size.width) / 2;
+    let dy = height.saturating_sub(size.height) / 2;
+    Dots::from_fn(width, height, |x, y| {
+        x >= dx
+            && y >= dy
+            && x - dx < size.width
+            && y - dy < size.height
+            && font
+                .image
+                .pixel(Point::new((left + x - dx) as i32, (top + y - dy) as i32))
+                == Some(BinaryColor::On)
+    })
+}
+
+/// Print settings, reset by `ESC @`
+#[derive(Clone)]
+struct State {
+    align: Alignment,
+    font: Font,
+    bold: bool,
+    double_strike: bool,
+    underline: u8,
+    reverse: bool,
+    upside_down: bool,
+    rotate: bool,
+    red: bool,
+    size: (u32, u32),
+    char_spacing: u32,
+    kanji_size: (u32, u32),
+    kanji_underline: u8,
+    line_space: u32,
+    left_margin: u32,
+    area_width: Option<u32>,
+    tab_stops: Vec<u8>,
+}
+
+impl Default for State {
+    fn default() -> State {
+        State {
+            align: Alignment::Left,
+            font: Font::A,
+            bold: false,
+            double_strike: false,
+            underline: 0,
+            reverse: false,
+            upside_down: false,
+            rotate: false,
+            red: false,
+            size: (1, 1),
+            char_spacing: 0,
+            kanji_size: (1, 1),
+            kanji_underline: 0,
+            line_space: LINE_SPACE,
+            left_margin: 0,
+            area_width: None,
+            tab_stops: (1..32).map(|n| n * 8).collect(),
+        }
+    }
+}
+
+/// A character or bit image in the line buffer
+struct Piece {
+    x: u32,
+    dots: Dots,
+    color: Rgb<u8>,
+    reverse: bool,
+}
+
+/// A virtual printer that prints on an image
+///
+/// Bytes written to the emulator are printed on [flush][io::Write::flush],
+/// so it can be the device of a [Printer][crate::printer::Printer].
+pub struct Emulator {
+    profile: Profile,
+    decoder: Decoder,
+    pending: Vec<u8>,
+    paper: RgbImage,
+    y: u32,
+    state: State,
+    line: Vec<Piece>,
+    x: u32,
+    macro_commands: Vec<Command>,
+}
+
+impl Emulator {
+    /// Paper `dot_width` dots wide, with the fonts of the default profile
+    pub fn new(dot_width: u32) -> Emulator {
+        let profile = Profile {
+            dot_width,
+            ..Profile::default()
+        };
+        let mut emulator = Emulator {
+            profile: Profile::default(),
+            decoder: Decoder::default(),
+            pending: vec![],
+            paper: RgbImage::from_pixel(dot_width, 0, WHITE),
+            y: 0,
+            state: State::default(),
+            line: vec![],
+            x: 0,
+            macro_commands: vec![],
+        };
+        emulator.set_profile(&profile);
+        emulator
+    }
+
+    /// Use the paper width, fonts and code pages of `profile`
+    pub fn set_profile(&mut self, profile: &Profile) {
+        self.profile = profile.clone();
+        self.decoder.set_profile(profile);
+        if self.paper.width() != profile.dot_width {
+            let mut paper = RgbImage::from_pixel(profile.dot_width, self.paper.height(), WHITE);
+            imageops::replace(&mut paper, &self.paper, 0, 0);
+            self.paper = paper;
+        }
+    }
+
+    /// Print `data`, a stream of complete commands
+    pub fn print(&mut self, data: &[u8]) {
+        for entry in self.decoder.decode(data) {
+            if let Item::Command(command) = entry.item {
+                self.command(&command);
+            }
+        }
+    }
+
+    /// Print `document` as encoded for the profile of the emulator
+    pub fn print_document(&mut self, document: &Document) -> io::Result<()> {
+        let mut encoder = Encoder::default();
+        encoder.set_profile(self.profile.clone());
+        let data = encoder.encode_document(document)?;
+        self.print(&data);
+        Ok(())
+    }
+
+    /// The paper printed so far, a line without line feed is not printed yet
+    pub fn image(&self) -> RgbImage {
+        imageops::crop_imm(&self.paper, 0, 0, self.paper.width(), self.y).to_image()
+    }
+
+    fn command(&mut self, command: &Command) {
+        let state = &mut self.state;
+        match *command {
+            Command::Init | Command::Reset => *state = State::default(),
+            Command::Text(ref content) => self.text(content, false),
+            Command::TextCjk(ref content) => self.text(content, true),
+            Command::KanjiMode {
+                double_width,
+                double_height,
+                underline,
+            } => {
+                state.kanji_size = (double_width as u32 + 1, double_height as u32 + 1);
+                state.kanji_underline = underline as u8;
+            }
+            Command::KanjiUnderline(n) => state.kanji_underline = n,
+            Command::KanjiQuadruple(n) => state.kanji_size = (n as u32 + 1, n as u32 + 1),
+            Command::LineSpace(n) => state.line_space = n.map_or(LINE_SPACE, u32::from),
+            Command::TabStops(ref stops) => state.tab_stops = stops.clone(),
+            Command::LeftMargin(n) => state.left_margin = n as u32,
+            Command::PrintAreaWidth(n) => state.area_width = Some(n as u32),
+            Command::Position(n) => self.x = n as u32,
+            Command::RelativePosition(n) => self.x = (self.x as i64 + n as i64).max(0) as u32,
+            Command::Feed(n) => (0..n).for_each(|_| self.print_line(None)),
+            Command::FeedDots(n) => self.print_line(Some(n as u32)),
+            Command::FeedLines(0) => self.print_line(Some(0)),
+            Command::FeedLines(n) => (0..n).for_each(|_| self.print_line(None)),
+            Command::ReverseFeedDots(n) => self.y = self.y.saturating_sub(n as u32),
+            Command::ReverseFeedLines(n) => {
+                self.y = self.y.saturating_sub(n as u32 * state.line_space)
+            }
+            Command::FeedToCut | Command::Control(Control::Lf) | Command::Control(Control::Ff) => {
+                self.print_line(None)
+            }
+            Command::Control(Control::Ht) => self.tab(),
+            Command::Align(align) => state.align = align,
+            Command::Font(font) => state.font = font,
+            Command::Bold(n) => state.bold = n,
+            Command::DoubleStrike(n) => state.double_strike = n,
+            Command::Underline(n) => state.underline = n.min(2),
+            Command::Reverse(n) => state.reverse = n,
+            Command::UpsideDown(n) => state.upside_down = n,
+            Command::Rotate(n) => state.rotate = n,
+            Command::SecondColor(n) => state.red = n,
+            Command::CharSpacing(n) => state.char_spacing = n as u32,
+            Command::Size { width, height } => {
+                state.size = (width.max(1) as u32, height.max(1) as u32)
+            }
+            Command::Barcode {
+                ref code,
+                kind,
+                text,
+                font,
+                width,
+                height,
+            } => self.barcode(code, kind, text, font, width, height),
+            Command::QrCode {
+                ref code,
+                version,
+                level,
+                size,
+            } => self.qr_code(code.as_bytes(), version, level, size),
+            Command::Image(ref image) => self.block(&Dots::from_image(image)),
+            Command::Raster { ref image, mode } => {
+                let (sx, sy) = match mode {
+                    RasterMode::Normal => (1, 1),
+                    RasterMode::DoubleWidth => (2, 1),
+                    RasterMode::DoubleHeight => (1, 2),
+                    RasterMode::Quadruple => (2, 2),
+                };
+                self.block(&Dots::from_image(image).scale(sx, sy))
+            }
+            // Single density doubles the dots horizontally, 8-dot images
+            // have a third of the vertical density of 24-dot images
+            Command::BitImage { ref image, density } => {
+                let (sx, sy) = match density {
+                    BitImageDensity::S8 => (2, 3),
+                    BitImageDensity::D8 => (1, 3),
+                    BitImageDensity::S24 => (2, 1),
+                    BitImageDensity::D24 => (1, 1),
+                };
+                self.put(Dots::from_image(image).scale(sx, sy), false)
+            }
+            Command::DefineMacro(ref commands) => self.macro_commands = commands.clone(),
+            Command::RunMacro { times, .. } => {
+                let commands = self.macro_commands.clone();
+                for _ in 0..times {
+                    commands
+                        .iter()
+                        .filter(|command| !matches!(command, Command::RunMacro { .. }))
+                        .for_each(|command| self.command(command));
+                }
+            }
+            Command::Cut { feed, .. } => self.cut(feed.unwrap_or(0) as u32),
+            _ => {}
+        }
+    }
+
+    /// Width of the printable area in dots
+    fn area_width(&self) -> u32 {
+        let width = self.paper.width().saturating_sub(self.state.left_margin);
+        self.state.area_width.map_or(width, |area| area.min(width))
+    }
+
+    /// Left edge of something `width` dots wide, aligned in the area
+    fn left(&self, width: u32) -> u32 {
+        let space = self.area_width().saturating_sub(width);
+        self.state.left_margin
+            + match self.state.align {
+                Alignment::Left => 0,
+                Alignment::Center => space / 2,
+                Alignment::Right => space,
+            }
+    }
+
+    /// Cell size in dots of `font`
+    fn cell(&self, font: Font) -> (u32, u32) {
+        self.profile
+            .font(font.name())
+            .map_or((12, 24), |metrics| (metrics.width, metrics.height))
+    }
+
+    /// Characters in the A cell size, `B` and `C` are smaller
+    fn font(&self, font: Font) -> (&'static MonoFont<'static>, u32, u32) {
+        let (width, height) = self.cell(font);
+        let mono = match font {
+            Font::A => &iso_8859_1::FONT_10X20,
+            _ => &iso_8859_1::FONT_9X15,
+        };
+        (mono, width, height)
+    }
+
+    fn text(&mut self, content: &str, cjk: bool) {
+        for c in content.chars() {
+            match c {
+                '\n' => self.print_line(None),
+                '\t' => self.tab(),
+                c => match c.width() {
+                    Some(0) | None => {}
+                    Some(width) => self.character(c, cjk || width > 1),
+                },
+            }
+        }
+    }
+
+    /// Put `c` in the line buffer, `wide` in the double width Kanji cell
+    fn character(&mut self, c: char, wide: bool) {
+        let state = &self.state;
+        let (dots, (sx, sy), underline) = if wide {
+            let (mono, width, height) = self.font(Font::A);
+            let dots = glyph(mono, c, width * 2, height);
+            (dots, state.kanji_size, state.kanji_underline)
+        } else {
+            let (mono, width, height) = self.font(state.font);
+            (glyph(mono, c, width, height), state.size, state.underline)
+        };
+        let dots = if state.bold || state.double_strike {
+            dots.bold()
+        } else {
+            dots
+        };
+        let dots = if state.rotate { dots.rotate() } else { dots };
+        let mut cell = Dots::new(dots.width + state.char_spacing, dots.height);
+        cell.draw(&dots, 0, 0);
+        let mut cell = cell.scale(sx, sy);
+        if !state.reverse {
+            for y in cell.height.saturating_sub(underline as u32)..cell.height {
+                for x in 0..cell.width {
+                    cell.set(x, y);
+                }
+            }
+        }
+        let reverse = state.reverse;
+        self.put(cell, reverse);
+    }
+
+    /// Add `dots` to the line buffer, printing the line first if full
+    fn put(&mut self, dots: Dots, reverse: bool) {
+        if self.x > 0 && self.x + dots.width > self.area_width() {
+            self.print_line(None);
+        }
+        self.line.push(Piece {
+            x: self.x,
+            color: if self.state.red { RED } else { BLACK },
+            dots,
+            reverse,
+        });
+        self.x += self.line[self.line.len() - 1].dots.width;
+    }
+
+    /// Move to the next tab stop, in columns of the current font
+    fn tab(&mut self) {
+        let (width, _) = self.cell(self.state.font);
+        let column = (width + self.state.char_spacing) * self.state.size.0;
+        let next = self
+            .state
+            .tab_stops
+            .iter()
+            .map(|&n| n as u32 * column)
+            .find(|&x| x > self.x);
+        match next {
+            Some(x) if x <= self.area_width() => self.x = x,
+            _ => {}
+        }
+    }
+
+    /// Print the line buffer and feed `feed` dots, or the line spacing
+    fn print_line(&mut self, feed: Option<u32>) {
+        let pieces = mem::take(&mut self.line);
+        self.x = 0;
+        // Pieces may end right of the position after moving back
+        let used = pieces
+            .iter()
+            .map(|piece| piece.x + piece.dots.width)
+            .max()
+            .unwrap_or(0);
+        let height = pieces
+            .iter()
+            .map(|piece| piece.dots.height)
+            .max()
+            .unwrap_or(0);
+        let left = self.left(used);
+        for piece in pieces {
+            // Upside down lines are rotated as a whole
+            if self.state.upside_down {
+                let x = left + used.saturating_sub(piece.x + piece.dots.width);
+                self.paint(&piece.dots.flip(), x, self.y, piece.color, piece.reverse);
+            } else {
+                let y = self.y + height - piece.dots.height;
+                self.paint(&piece.dots, left + piece.x, y, piece.color, piece.reverse);
+            }
+        }
+        self.feed(feed.unwrap_or(self.state.line_space).max(height));
+    }
+
+    /// Print `dots` below the current line, aligned like text
+    fn block(&mut self, dots: &Dots) {
+        if !self.line.is_empty() {
+            self.print_line(None);
+        }
+        let color = if self.state.red { RED } else { BLACK };
+        self.paint(dots, self.left(dots.width), self.y, color, false);
+        self.feed(dots.height);
+    }
+
+    fn barcode(
+        &mut self,
+        code: &str,
+        kind: BarcodeKind,
+        text: BarcodeText,
+        font: Font,
+        width: u8,
+        height: u8,
+    ) {
+        let (widths, hri) = match barcode::bars(kind, code, width.clamp(1, 6) as u32) {
+            Some(bars) => bars,
+            None => return,
+        };
+        let mut bars = Dots::new(widths.iter().sum(), height.max(1) as u32);
+        let mut x = 0;
+        for (i, width) in widths.into_iter().enumerate() {
+            if i % 2 == 0 {
+                for y in 0..bars.height {
+                    (x..x + width).for_each(|x| bars.set(x, y));
+                }
+            }
+            x += width;
+        }
+        // Barcodes wider than the print area are not printed
+        if bars.width > self.area_width() {
+            return;
+        }
+        let (mono, width, height) = self.font(font);
+        let mut label = Dots::new(hri.chars().count() as u32 * width, height);
+        for (i, c) in hri.chars().enumerate() {
+            label.draw(&glyph(mono, c, width, height), i as u32 * width, 0);
+        }
+        let parts = match text {
+            BarcodeText::Off => vec![bars],
+            BarcodeText::Above => vec![label, bars],
+            BarcodeText::Below => vec![bars, label],
+            BarcodeText::Both => vec![label.clone(), bars, label],
+        };
+        self.block(&Dots::stack(&parts));
+    }
+
+    fn qr_code(&mut self, data: &[u8], version: u8, level: QrLevel, size: u8) {
+        let level = match level {
+            QrLevel::L => EcLevel::L,
+            QrLevel::M => EcLevel::M,
+            QrLevel::Q => EcLevel::Q,
+            QrLevel::H => EcLevel::H,
+        };
+        // Version 0 picks the smallest version that fits
+        let code = match version {
+            1..=40 => QrCode::with_version(data, Version::Normal(version as i16), level)
+                .or_else(|_| QrCode::with_error_correction_level(data, level)),
+            _ => QrCode::with_error_correction_level(data, level),
+        };
+        if let Ok(code) = code {
+            let width = code.width() as u32;
+            let dots = Dots::from_fn(width, width, |x, y| {
+                code[(x as usize, y as usize)] == qrcode::Color::Dark
+            });
+            // Printers support modules of 1 to 16 dots
+            let size = size.clamp(1, 16) as u32;
+            self.block(&dots.scale(size, size));
+        }
+    }
+
+    /// Feed `feed` dots and draw a dashed line where the paper is cut
+    fn cut(&mut self, feed: u32) {
+        if !self.line.is_empty() {
+            self.print_line(None);
+        }
+        self.feed(feed + CUT_GAP / 2);
+        for x in (0..self.paper.width()).filter(|x| x % 8 < 4) {
+            self.paper.put_pixel(x, self.y, BLACK);
+        }
+        self.feed(CUT_GAP / 2);
+    }
+
+    fn paint(&mut self, dots: &Dots, x: u32, y: u32, color: Rgb<u8>, reverse: bool) {
+        self.grow(y + dots.height);
+        let width = self.paper.width().saturating_sub(x).min(dots.width);
+        for dy in 0..dots.height {
+            for dx in 0..width {
+                if dots.get(dx, dy) != reverse {
+                    self.paper.put_pixel(x + dx, y + dy, color);
+                }
+            }
+        }
+    }
+
+    fn feed(&mut self, dots: u32) {
+        self.y += dots;
+        self.grow(self.y + 1);
+    }
+
+    /// Make the paper at least `height` dots long
+    fn grow(&mut self, height: u32) {
+        if height > self.paper.height() {
+            let height = height.max(self.paper.height() * 2);
+            let mut paper = RgbImage::from_pixel(self.paper.width(), height, WHITE);
+            imageops::replace(&mut paper, &self.paper, 0, 0);
+            self.paper = paper;
+        }
+    }
+}
+
+impl io::Write for Emulator {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let data = mem::take(&mut self.pending);
+        self.print(&data);
+        Ok(())
+    }
+}
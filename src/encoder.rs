@@ -0,0 +1,970 @@
+//! Encode [Command]s into ESC/POS bytes
+//!
+//! The [Encoder] keeps the state the bytes depend on: codec and code page
+//! of text, the profile commands are checked against and the text
+//! attributes already active, which are not sent again.
+//!
+//! ```rust
+//! use escposify::command::Command;
+//! use escposify::encoder::Encoder;
+//!
+//! let mut encoder = Encoder::default();
+//! assert_eq!(encoder.encode(&Command::Bold(true)).unwrap(), b"\x1b\x45\x01");
+//! assert_eq!(encoder.encode(&Command::Bold(true)).unwrap(), b"");
+//! ```
+
+use std::borrow::Cow;
+use std::io;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use byteorder::{LittleEndian, WriteBytesExt};
+use encoding::all::{encodings, BIG5_2003, EUC_JP, GBK, UTF_8, WINDOWS_31J, WINDOWS_949};
+use encoding::label::encoding_from_whatwg_label;
+use encoding::types::{EncoderTrap, EncodingRef};
+
+use crate::codepage::CodePages;
+use crate::command::{
+    BarcodeKind, BarcodeText, BitImageDensity, Command, Control, CutMode, Document, DrawerPin,
+    KanjiEncoding, MacroMode, QrLevel, RasterMode,
+};
+use crate::consts;
+use crate::img::Image;
+use crate::page::{Area, Direction};
+use crate::profile::{Graphics, Profile};
+use crate::style::{Font, TextStyle};
+use crate::translit::Transliterator;
+
+/// Size of the printer's macro buffer in bytes
+pub const MACRO_BUFFER_SIZE: usize = 2048;
+
+/// Encoder state restored after a macro definition
+pub(crate) struct Snapshot {
+    codec: EncodingRef,
+    code_page: Option<u8>,
+    charset: Option<u8>,
+    kanji: KanjiEncoding,
+    state: TextStyle,
+}
+
+fn profile_code_pages(profile: &Profile) -> CodePages {
+    CodePages::new(
+        profile
+            .code_pages
+            .iter()
+            .map(|(number, label)| (*number, label.as_str())),
+    )
+}
+
+fn unsupported(profile: &Profile, what: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{} is not supported by profile {}", what, profile.name),
+    )
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// The codec named `name`, by encoding name or WHATWG label
+pub(crate) fn codec_by_name(name: &str) -> io::Result<EncodingRef> {
+    encodings()
+        .iter()
+        .find(|codec| codec.name().eq_ignore_ascii_case(name))
+        .copied()
+        .or_else(|| encoding_from_whatwg_label(name))
+        .ok_or_else(|| invalid(format!("Unknown encoding: {}", name)))
+}
+
+/// `duration` in multiples of `unit`, rounded down and checked to be in
+/// `range`
+fn duration_units(duration: Duration, unit: Duration, range: RangeInclusive<u8>) -> io::Result<u8> {
+    let units = duration.as_millis() / unit.as_millis();
+    match u8::try_from(units) {
+        Ok(units) if range.contains(&units) => Ok(units),
+        _ => Err(invalid(format!(
+            "Invalid duration: {:?} ({:?} to {:?})",
+            duration,
+            unit * *range.start() as u32,
+            unit * *range.end() as u32
+        ))),
+    }
+}
+
+fn buzzer_count(times: u8) -> io::Result<u8> {
+    if !(1..=63).contains(&times) {
+        return Err(invalid(format!("Invalid buzzer count: {} (1 - 63)", times)));
+    }
+    Ok(times)
+}
+
+/// `m` of the drawer kick commands
+fn drawer_pin(pin: DrawerPin) -> u8 {
+    match pin {
+        DrawerPin::Pin2 => 0,
+        DrawerPin::Pin5 => 1,
+    }
+}
+
+fn push_u16le(out: &mut Vec<u8>, n: u16) {
+    out.write_u16::<LittleEndian>(n)
+        .expect("writing to a Vec can't fail");
+}
+
+pub(crate) fn nested_macro() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "Macros cannot be nested")
+}
+
+/// `GS :` macro definition of `recording`
+pub(crate) fn wrap_macro(recording: &[u8], out: &mut Vec<u8>) -> io::Result<()> {
+    if recording.len() > MACRO_BUFFER_SIZE {
+        return Err(invalid(format!(
+            "Macro of {} bytes exceeds the {} bytes macro buffer",
+            recording.len(),
+            MACRO_BUFFER_SIZE
+        )));
+    }
+    out.extend_from_slice(consts::MACRO_DEFINE);
+    out.extend_from_slice(recording);
+    out.extend_from_slice(consts::MACRO_DEFINE);
+    Ok(())
+}
+
+/// Turns commands into bytes for a printer [Profile]
+pub struct Encoder {
+    pub(crate) codec: EncodingRef,
+    trap: EncoderTrap,
+    profile: Profile,
+    code_pages: CodePages,
+    pub(crate) code_page: Option<u8>,
+    pub(crate) charset: Option<u8>,
+    auto_code_page: bool,
+    kanji: KanjiEncoding,
+    transliterator: Option<Transliterator>,
+    pub(crate) state: TextStyle,
+    /// Page mode print area, the whole page if not set
+    page_area: Option<Area>,
+    page_direction: Direction,
+}
+
+impl Encoder {
+    /// Text is encoded with `codec` (UTF-8 by default), characters it can't
+    /// encode are handled by `trap` (replaced by default)
+    pub fn new(codec: Option<EncodingRef>, trap: Option<EncoderTrap>) -> Encoder {
+        let profile = Profile::default();
+        Encoder {
+            codec: codec.unwrap_or(UTF_8 as EncodingRef),
+            trap: trap.unwrap_or(EncoderTrap::Replace),
+            code_pages: profile_code_pages(&profile),
+            profile,
+            code_page: None,
+            charset: None,
+            auto_code_page: false,
+            kanji: KanjiEncoding::Gb2312,
+            transliterator: None,
+            state: TextStyle::new(),
+            page_area: None,
+            page_direction: Direction::LeftToRight,
+        }
+    }
+
+    /// Capability profile of the printer, [Profile::default] unless set
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    /// Use the capabilities of `profile`, see
+    /// [Printer::set_profile][crate::printer::Printer::set_profile]
+    pub fn set_profile(&mut self, profile: Profile) {
+        self.code_pages = profile_code_pages(&profile);
+        self.profile = profile;
+    }
+
+    /// See [Printer::set_auto_code_page][crate::printer::Printer::set_auto_code_page]
+    pub fn set_auto_code_page(&mut self, enabled: bool) {
+        self.auto_code_page = enabled;
+    }
+
+    /// See [Printer::set_transliterator][crate::printer::Printer::set_transliterator]
+    pub fn set_transliterator(&mut self, transliterator: Option<Transliterator>) {
+        self.transliterator = transliterator;
+    }
+
+    /// Current text state, `None` for values that are unknown because they
+    /// were never set
+    pub fn state(&self) -> &TextStyle {
+        &self.state
+    }
+
+    /// Characters per line with the current font and character width
+    pub fn columns(&self) -> u32 {
+        let font = self.state.font.unwrap_or(Font::A);
+        let (width, _) = self.state.size.unwrap_or((1, 1));
+        let columns = self
+            .profile
+            .font(font.name())
+            .or_else(|| self.profile.fonts.first())
+            .map_or(0, |font| font.columns);
+        columns / width as u32
+    }
+
+    pub(crate) fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            codec: self.codec,
+            code_page: self.code_page,
+            charset: self.charset,
+            kanji: self.kanji,
+            state: self.state,
+        }
+    }
+
+    pub(crate) fn restore(&mut self, snapshot: Snapshot) {
+        self.codec = snapshot.codec;
+        self.code_page = snapshot.code_page;
+        self.charset = snapshot.charset;
+        self.kanji = snapshot.kanji;
+        self.state = snapshot.state;
+    }
+
+    /// Bytes of `command`, nothing for attributes already active
+    pub fn encode(&mut self, command: &Command) -> io::Result<Vec<u8>> {
+        let mut out = vec![];
+        self.encode_into(command, &mut out)?;
+        Ok(out)
+    }
+
+    /// Bytes of every command of `document`
+    pub fn encode_document(&mut self, document: &Document) -> io::Result<Vec<u8>> {
+        let mut out = vec![];
+        for command in document {
+            self.encode_into(command, &mut out)?;
+        }
+        Ok(out)
+    }
+
+    fn can_encode(&self, c: char) -> bool {
+        if self.auto_code_page {
+            return self
+                .code_pages
+                .iter()
+                .any(|page| page.encode_char(c).is_some());
+        }
+        if let Some(page) = self.code_page.and_then(|n| self.code_pages.get(n)) {
+            return page.encode_char(c).is_some();
+        }
+        let mut buf = [0; 4];
+        self.codec
+            .encode(c.encode_utf8(&mut buf), EncoderTrap::Strict)
+            .is_ok()
+    }
+
+    fn transliterate<'a>(&self, content: &'a str) -> Cow<'a, str> {
+        match self.transliterator {
+            Some(ref transliterator) => {
+                Cow::Owned(transliterator.apply(content, |c| self.can_encode(c)))
+            }
+            None => Cow::Borrowed(content),
+        }
+    }
+
+    fn encode_text(&self, content: &str) -> io::Result<Vec<u8>> {
+        if let Some(page) = self.code_page.and_then(|n| self.code_pages.get(n)) {
+            return page.encode(content, self.trap);
+        }
+        self.codec
+            .encode(content, self.trap)
+            .map_err(|err| invalid(err.to_string()))
+    }
+
+    fn text(&mut self, content: &str, out: &mut Vec<u8>) -> io::Result<()> {
+        let content = self.transliterate(content);
+        let content = content.as_ref();
+        if self.auto_code_page {
+            for (page, bytes) in self.code_pages.encode(content, self.code_page, self.trap)? {
+                if let Some(page) = page {
+                    self.code_page(page, out)?;
+                }
+                out.extend_from_slice(&bytes);
+            }
+            return Ok(());
+        }
+        let rv = self.encode_text(content)?;
+        out.extend_from_slice(&rv);
+        Ok(())
+    }
+
+    fn code_page(&mut self, n: u8, out: &mut Vec<u8>) -> io::Result<()> {
+        if !self.profile.code_pages.contains_key(&n) {
+            return Err(unsupported(&self.profile, &format!("Code page {}", n)));
+        }
+        self.code_page = Some(n);
+        out.extend_from_slice(consts::CODE_PAGE);
+        out.push(n);
+        Ok(())
+    }
+
+    fn kanji_codec(&self) -> EncodingRef {
+        match self.kanji {
+            KanjiEncoding::Jis => EUC_JP as EncodingRef,
+            KanjiEncoding::ShiftJis => WINDOWS_31J,
+            KanjiEncoding::Gb2312 => GBK,
+            KanjiEncoding::Big5 => BIG5_2003,
+            KanjiEncoding::Ksc5601 => WINDOWS_949,
+        }
+    }
+
+    /// Whether `c` prints from the current single-byte character table
+    fn in_code_page(&self, c: char) -> bool {
+        if c.is_ascii() {
+            return true;
+        }
+        if self.auto_code_page || self.code_page.is_some() {
+            return self.can_encode(c);
+        }
+        let mut buf = [0; 4];
+        self.codec
+            .encode(c.encode_utf8(&mut buf), EncoderTrap::Strict)
+            .is_ok_and(|bytes| bytes.len() == 1)
+    }
+
+    /// Whether `c` is sent in Kanji mode: it has a multi-byte code in the
+    /// Kanji encoding but isn't in the current code page
+    fn is_kanji(&self, c: char) -> bool {
+        let mut buf = [0; 4];
+        !self.in_code_page(c)
+            && self
+                .kanji_codec()
+                .encode(c.encode_utf8(&mut buf), EncoderTrap::Strict)
+                .is_ok_and(|bytes| bytes.len() > 1)
+    }
+
+    fn encode_kanji(&self, content: &str) -> io::Result<Vec<u8>> {
+        let codec = self.kanji_codec();
+        let rv = codec
+            .encode(content, self.trap)
+            .map_err(|err| invalid(err.to_string()))?;
+        if self.kanji != KanjiEncoding::Jis {
+            return Ok(rv);
+        }
+        // JIS is EUC-JP without the high bits, single-byte kana and
+        // JIS X 0212 (SS2 / SS3 prefixed) have no JIS equivalent
+        let mut jis = Vec::with_capacity(rv.len());
+        let mut i = 0;
+        while i < rv.len() {
+            match rv[i] {
+                0x8e => {
+                    jis.extend_from_slice(b"\x21\x29");
+                    i += 2;
+                }
+                0x8f => {
+                    jis.extend_from_slice(b"\x21\x29");
+                    i += 3;
+                }
+                b if b >= 0xa1 && i + 1 < rv.len() => {
+                    jis.push(b & 0x7f);
+                    jis.push(rv[i + 1] & 0x7f);
+                    i += 2;
+                }
+                b => {
+                    jis.push(b);
+                    i += 1;
+                }
+            }
+        }
+        Ok(jis)
+    }
+
+    fn text_cjk(&mut self, content: &str, out: &mut Vec<u8>) -> io::Result<()> {
+        let mut rest = content;
+        while !rest.is_empty() {
+            let kanji = rest.chars().next().is_some_and(|c| self.is_kanji(c));
+            let split = rest
+                .char_indices()
+                .find(|&(_, c)| self.is_kanji(c) != kanji)
+                .map_or(rest.len(), |(i, _)| i);
+            let (run, tail) = rest.split_at(split);
+            if !kanji {
+                self.text(run, out)?;
+            } else {
+                let bytes = self.encode_kanji(run)?;
+                out.extend_from_slice(consts::KANJI_ON);
+                out.extend_from_slice(&bytes);
+                out.extend_from_slice(consts::KANJI_OFF);
+            }
+            rest = tail;
+        }
+        Ok(())
+    }
+
+    fn check_dots(&self, what: &str, dots: u16) -> io::Result<()> {
+        if dots as u32 > self.profile.dot_width {
+            return Err(invalid(format!(
+                "{} of {} dots exceeds the printable width of {} dots",
+                what, dots, self.profile.dot_width
+            )));
+        }
+        Ok(())
+    }
+
+    fn check_page_area(&self, area: Area) -> io::Result<()> {
+        let dot_width = self.profile.dot_width;
+        if area.width == 0 || area.height == 0 || area.x as u32 + area.width as u32 > dot_width {
+            return Err(invalid(format!(
+                "Invalid page area {:?} for a printable width of {} dots",
+                area, dot_width
+            )));
+        }
+        match self.profile.page_height {
+            Some(page_height) if area.y as u32 + area.height as u32 > page_height => {
+                Err(invalid(format!(
+                    "Invalid page area {:?} for a page height of {} dots",
+                    area, page_height
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that `(x, y)` is inside the page area, in the print direction
+    fn check_page_position(&self, x: u16, y: u16) -> io::Result<()> {
+        let (width, height) = match self.page_area {
+            Some(area) => (area.width as u32, area.height as u32),
+            None => (
+                self.profile.dot_width,
+                self.profile.page_height.unwrap_or(u32::MAX),
+            ),
+        };
+        let (width, height) = if self.page_direction.is_vertical() {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        if x as u32 >= width || y as u32 >= height {
+            return Err(invalid(format!(
+                "Position ({}, {}) outside of the {}x{} page area",
+                x, y, width, height
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether the profile has a buzzer, warns if not
+    fn has_buzzer(&self) -> bool {
+        if !self.profile.buzzer {
+            log::warn!(
+                "Buzzer not supported by profile {}, ignored",
+                self.profile.name
+            );
+        }
+        self.profile.buzzer
+    }
+
+    /// Send `command` followed by `enabled` unless already active
+    fn switch(
+        &mut self,
+        command: &[u8],
+        state: fn(&mut TextStyle) -> &mut Option<bool>,
+        enabled: bool,
+        out: &mut Vec<u8>,
+    ) {
+        let current = state(&mut self.state);
+        if *current == Some(enabled) {
+            return;
+        }
+        *current = Some(enabled);
+        out.extend_from_slice(command);
+        out.push(enabled as u8);
+    }
+
+    /// Emulate a QR code with a raster image on printers without `QR` support
+    fn qrcode_image(&mut self, code: &str, out: &mut Vec<u8>) -> io::Result<()> {
+        #[cfg(feature = "qrcode_builder")]
+        {
+            let width = self.profile.dot_width / 2;
+            let image = Image::from_qr(code, width).map_err(|err| invalid(err.to_string()))?;
+            self.encode_into(&Command::Image(image), out)
+        }
+        #[cfg(not(feature = "qrcode_builder"))]
+        {
+            let _ = (code, out);
+            Err(unsupported(&self.profile, "QR code"))
+        }
+    }
+
+    fn bit_image(&mut self, image: &Image, density: BitImageDensity, out: &mut Vec<u8>) {
+        let header = match density {
+            BitImageDensity::S8 => consts::BITMAP_S8,
+            BitImageDensity::D8 => consts::BITMAP_D8,
+            BitImageDensity::S24 => consts::BITMAP_S24,
+            BitImageDensity::D24 => consts::BITMAP_D24,
+        };
+        let n = density.dots() / 8;
+        out.extend_from_slice(consts::LS_SET);
+        out.push(0);
+        for line in image.bitimage_lines(n * 8) {
+            out.extend_from_slice(header);
+            push_u16le(out, (line.len() / n as usize) as u16);
+            out.extend_from_slice(&line);
+            out.extend_from_slice(consts::EOL.as_bytes());
+        }
+    }
+
+    fn raster(&mut self, image: &Image, mode: RasterMode, out: &mut Vec<u8>) {
+        let header = match mode {
+            RasterMode::Normal => consts::GSV0_NORMAL,
+            RasterMode::DoubleWidth => consts::GSV0_DW,
+            RasterMode::DoubleHeight => consts::GSV0_DH,
+            RasterMode::Quadruple => consts::GSV0_DWDH,
+        };
+        out.extend_from_slice(header);
+        push_u16le(out, ((image.width + 7) / 8) as u16);
+        push_u16le(out, image.height as u16);
+        out.extend_from_slice(&image.get_raster());
+    }
+
+    fn define_macro(&mut self, commands: &[Command], out: &mut Vec<u8>) -> io::Result<()> {
+        let snapshot = self.snapshot();
+        let mut recording = vec![];
+        let result = commands.iter().try_for_each(|command| match command {
+            Command::DefineMacro(_) => Err(nested_macro()),
+            command => self.encode_into(command, &mut recording),
+        });
+        self.restore(snapshot);
+        result?;
+        wrap_macro(&recording, out)
+    }
+
+    fn encode_into(&mut self, command: &Command, out: &mut Vec<u8>) -> io::Result<()> {
+        match *command {
+            Command::Raw(ref bytes) => out.extend_from_slice(bytes),
+            Command::Init => {
+                // Track the printer state reset by `ESC @`
+                self.code_page = None;
+                self.charset = None;
+                self.state = TextStyle::plain();
+                self.page_area = None;
+                self.page_direction = Direction::LeftToRight;
+                out.extend_from_slice(consts::HW_INIT);
+            }
+            Command::Select => out.extend_from_slice(consts::HW_SELECT),
+            Command::Reset => out.extend_from_slice(consts::HW_RESET),
+            Command::Text(ref content) => self.text(content, out)?,
+            Command::TextCjk(ref content) => self.text_cjk(content, out)?,
+            Command::CodePage(n) => self.code_page(n, out)?,
+            Command::Codec(ref name) => {
+                let codec = codec_by_name(name)?;
+                self.codec = codec;
+                let page = self
+                    .code_pages
+                    .iter()
+                    .find(|page| page.is_codec(codec))
+                    .map(|page| page.number);
+                match page {
+                    Some(n) => self.code_page(n, out)?,
+                    // Back to the `ESC @` default table for the codec's bytes
+                    None => {
+                        if self.code_page.take().is_some_and(|n| n != 0) {
+                            out.extend_from_slice(consts::CODE_PAGE);
+                            out.push(0);
+                        }
+                    }
+                }
+            }
+            Command::Charset(n) => {
+                self.charset = Some(n);
+                out.extend_from_slice(consts::CHARSET);
+                out.push(n);
+            }
+            Command::KanjiEncoding(kanji) => {
+                self.kanji = kanji;
+                match kanji {
+                    KanjiEncoding::Jis => out.extend_from_slice(consts::KANJI_CODE_JIS),
+                    KanjiEncoding::ShiftJis => out.extend_from_slice(consts::KANJI_CODE_SJIS),
+                    _ => {}
+                }
+            }
+            Command::KanjiMode {
+                double_width,
+                double_height,
+                underline,
+            } => {
+                let mut mode = 0;
+                if double_width {
+                    mode |= 0x04;
+                }
+                if double_height {
+                    mode |= 0x08;
+                }
+                if underline {
+                    mode |= 0x80;
+                }
+                out.extend_from_slice(consts::KANJI_MODE);
+                out.push(mode);
+            }
+            Command::KanjiUnderline(n) => {
+                if n > 2 {
+                    return Err(invalid(format!("Invalid Kanji underline: {}", n)));
+                }
+                out.extend_from_slice(consts::KANJI_UNDERLINE);
+                out.push(n);
+            }
+            Command::KanjiQuadruple(enabled) => {
+                out.extend_from_slice(consts::KANJI_QUADRUPLE);
+                out.push(enabled as u8);
+            }
+            Command::LineSpace(Some(n)) => {
+                out.extend_from_slice(consts::LS_SET);
+                out.push(n);
+            }
+            Command::LineSpace(None) => out.extend_from_slice(consts::LS_DEFAULT),
+            Command::TabStops(ref stops) => {
+                if stops.len() > 32 {
+                    return Err(invalid(format!(
+                        "Too many tab stops: {} (max 32)",
+                        stops.len()
+                    )));
+                }
+                if stops.contains(&0) || stops.windows(2).any(|pair| pair[0] >= pair[1]) {
+                    return Err(invalid(format!(
+                        "Tab stops must be ascending and non-zero: {:?}",
+                        stops
+                    )));
+                }
+                out.extend_from_slice(consts::TAB_STOPS);
+                out.extend_from_slice(stops);
+                out.push(0);
+            }
+            Command::LeftMargin(dots) => {
+                self.check_dots("Left margin", dots)?;
+                out.extend_from_slice(consts::POS_LEFT_MARGIN);
+                push_u16le(out, dots);
+            }
+            Command::PrintAreaWidth(dots) => {
+                self.check_dots("Printing area width", dots)?;
+                out.extend_from_slice(consts::POS_AREA_WIDTH);
+                push_u16le(out, dots);
+            }
+            Command::Position(dots) => {
+                self.check_dots("Position", dots)?;
+                out.extend_from_slice(consts::POS_ABSOLUTE);
+                push_u16le(out, dots);
+            }
+            Command::RelativePosition(dots) => {
+                out.extend_from_slice(consts::POS_RELATIVE);
+                push_u16le(out, dots as u16);
+            }
+            Command::Feed(n) => out.extend_from_slice(consts::EOL.repeat(n).as_bytes()),
+            Command::FeedDots(n) => {
+                out.extend_from_slice(consts::PAPER_FEED_DOTS);
+                out.push(n);
+            }
+            Command::FeedLines(n) => {
+                out.extend_from_slice(consts::PAPER_FEED_LINES);
+                out.push(n);
+            }
+            Command::ReverseFeedDots(n) | Command::ReverseFeedLines(n) => {
+                if !self.profile.reverse_feed {
+                    return Err(unsupported(&self.profile, "Reverse feed"));
+                }
+                out.extend_from_slice(match *command {
+                    Command::ReverseFeedDots(_) => consts::PAPER_REVERSE_DOTS,
+                    _ => consts::PAPER_REVERSE_LINES,
+                });
+                out.push(n);
+            }
+            Command::FeedToCut => {
+                if !self.profile.cutter {
+                    return Err(unsupported(&self.profile, "Cutter"));
+                }
+                out.extend_from_slice(consts::PAPER_FEED_TO_CUT);
+            }
+            Command::Control(ctrl) => out.extend_from_slice(match ctrl {
+                Control::Lf => consts::CTL_LF,
+                Control::Ff => consts::CTL_FF,
+                Control::Cr => consts::CTL_CR,
+                Control::Ht => consts::CTL_HT,
+                Control::Vt => consts::CTL_VT,
+            }),
+            Command::Align(align) => {
+                if self.state.align != Some(align) {
+                    self.state.align = Some(align);
+                    out.extend_from_slice(align.command());
+                }
+            }
+            Command::Font(font) => {
+                if self.profile.font(font.name()).is_none() {
+                    return Err(unsupported(&self.profile, &format!("Font {}", font.name())));
+                }
+                if self.state.font != Some(font) {
+                    self.state.font = Some(font);
+                    out.extend_from_slice(font.command());
+                }
+            }
+            Command::Bold(enabled) => {
+                if self.state.bold != Some(enabled) {
+                    self.state.bold = Some(enabled);
+                    out.extend_from_slice(if enabled {
+                        consts::TXT_BOLD_ON
+                    } else {
+                        consts::TXT_BOLD_OFF
+                    });
+                }
+            }
+            Command::Underline(n) => {
+                let value = match n {
+                    0 => consts::TXT_UNDERL_OFF,
+                    1 => consts::TXT_UNDERL_ON,
+                    2 => consts::TXT_UNDERL2_ON,
+                    _ => return Err(invalid(format!("Invalid underline: {}", n))),
+                };
+                if self.state.underline != Some(n) {
+                    self.state.underline = Some(n);
+                    out.extend_from_slice(value);
+                }
+            }
+            Command::Reverse(enabled) => {
+                self.switch(consts::TXT_REVERSE, |s| &mut s.reverse, enabled, out)
+            }
+            Command::UpsideDown(enabled) => self.switch(
+                consts::TXT_UPSIDE_DOWN,
+                |s| &mut s.upside_down,
+                enabled,
+                out,
+            ),
+            Command::Rotate(enabled) => {
+                self.switch(consts::TXT_ROTATE, |s| &mut s.rotate, enabled, out)
+            }
+            Command::DoubleStrike(enabled) => self.switch(
+                consts::TXT_DOUBLE_STRIKE,
+                |s| &mut s.double_strike,
+                enabled,
+                out,
+            ),
+            Command::Smoothing(enabled) => {
+                self.switch(consts::TXT_SMOOTHING, |s| &mut s.smoothing, enabled, out)
+            }
+            Command::CharSpacing(n) => {
+                if self.state.spacing != Some(n) {
+                    self.state.spacing = Some(n);
+                    out.extend_from_slice(consts::TXT_SPACING);
+                    out.push(n);
+                }
+            }
+            Command::SecondColor(enabled) => {
+                self.switch(consts::TXT_COLOR, |s| &mut s.second_color, enabled, out)
+            }
+            Command::Size { width, height } => {
+                if width > 8 || height > 8 {
+                    return Err(invalid(format!(
+                        "Invalid character size: {}x{}",
+                        width, height
+                    )));
+                }
+                let size = (width.max(1), height.max(1));
+                if self.state.size != Some(size) {
+                    self.state.size = Some(size);
+                    out.extend_from_slice(consts::TXT_SIZE);
+                    out.push(((size.0 - 1) << 4) | (size.1 - 1));
+                }
+            }
+            Command::Barcode {
+                ref code,
+                kind,
+                text,
+                font,
+                width,
+                height,
+            } => {
+                if !self.profile.supports_barcode(kind.name()) {
+                    return Err(unsupported(
+                        &self.profile,
+                        &format!("Barcode {}", kind.name()),
+                    ));
+                }
+                if !(2..=6).contains(&width) || height == 0 {
+                    return Err(invalid(format!(
+                        "Invalid barcode size: width {} (2 - 6), height {}",
+                        width, height
+                    )));
+                }
+                out.extend_from_slice(consts::BARCODE_SET_WIDTH);
+                out.push(width);
+                out.extend_from_slice(consts::BARCODE_SET_HEIGHT);
+                out.push(height);
+                out.extend_from_slice(match font {
+                    Font::B => consts::BARCODE_FONT_B,
+                    _ => consts::BARCODE_FONT_A,
+                });
+                out.extend_from_slice(match text {
+                    BarcodeText::Off => consts::BARCODE_TXT_OFF,
+                    BarcodeText::Above => consts::BARCODE_TXT_ABV,
+                    BarcodeText::Below => consts::BARCODE_TXT_BLW,
+                    BarcodeText::Both => consts::BARCODE_TXT_BTH,
+                });
+                out.extend_from_slice(match kind {
+                    BarcodeKind::UpcA => consts::BARCODE_UPC_A,
+                    BarcodeKind::UpcE => consts::BARCODE_UPC_E,
+                    BarcodeKind::Ean13 => consts::BARCODE_EAN13,
+                    BarcodeKind::Ean8 => consts::BARCODE_EAN8,
+                    BarcodeKind::Code39 => consts::BARCODE_CODE39,
+                    BarcodeKind::Itf => consts::BARCODE_ITF,
+                    BarcodeKind::Nw7 => consts::BARCODE_NW7,
+                });
+                out.extend_from_slice(code.as_bytes());
+                out.push(0);
+            }
+            Command::QrCode {
+                ref code,
+                version,
+                level,
+                size,
+            } => {
+                if !self.profile.supports_2d("QR") {
+                    return self.qrcode_image(code, out);
+                }
+                let code_len = u16::try_from(code.len())
+                    .map_err(|_| invalid(format!("QR code too long: {} bytes", code.len())))?;
+                out.extend_from_slice(consts::TYPE_QR);
+                out.extend_from_slice(consts::CODE2D);
+                out.push(version);
+                out.extend_from_slice(match level {
+                    QrLevel::L => consts::QR_LEVEL_L,
+                    QrLevel::M => consts::QR_LEVEL_M,
+                    QrLevel::Q => consts::QR_LEVEL_Q,
+                    QrLevel::H => consts::QR_LEVEL_H,
+                });
+                out.push(size);
+                push_u16le(out, code_len);
+                out.extend_from_slice(code.as_bytes());
+            }
+            Command::Image(ref image) => match self.profile.graphics {
+                Graphics::Raster => self.raster(image, RasterMode::Normal, out),
+                Graphics::BitImage => self.bit_image(image, BitImageDensity::D24, out),
+            },
+            Command::BitImage { ref image, density } => self.bit_image(image, density, out),
+            Command::Raster { ref image, mode } => self.raster(image, mode, out),
+            Command::CashDraw(pin) => out.extend_from_slice(match pin {
+                DrawerPin::Pin2 => consts::CD_KICK_2,
+                DrawerPin::Pin5 => consts::CD_KICK_5,
+            }),
+            Command::DrawerPulse { pin, on_ms, off_ms } => {
+                if on_ms > 510 || off_ms > 510 {
+                    return Err(invalid(format!(
+                        "Invalid drawer pulse time: {}/{} ms (max 510)",
+                        on_ms, off_ms
+                    )));
+                }
+                out.extend_from_slice(consts::CD_PULSE);
+                out.extend_from_slice(&[drawer_pin(pin), (on_ms / 2) as u8, (off_ms / 2) as u8]);
+            }
+            Command::DrawerPulseRealtime { pin, on_ms } => {
+                if !(100..=800).contains(&on_ms) {
+                    return Err(invalid(format!(
+                        "Invalid drawer pulse time: {} ms (100 - 800)",
+                        on_ms
+                    )));
+                }
+                out.extend_from_slice(consts::CD_REALTIME_PULSE);
+                out.extend_from_slice(&[drawer_pin(pin), (on_ms / 100) as u8]);
+            }
+            Command::Beep { times, duration } => {
+                if !(1..=9).contains(&times) {
+                    return Err(invalid(format!("Invalid beep count: {} (1 - 9)", times)));
+                }
+                let t = duration_units(duration, Duration::from_millis(50), 1..=9)?;
+                if self.has_buzzer() {
+                    out.extend_from_slice(consts::BUZZER_BEEP);
+                    out.extend_from_slice(&[times, t]);
+                }
+            }
+            Command::Buzzer {
+                pattern,
+                times,
+                pause,
+            } => {
+                let c = buzzer_count(times)?;
+                let t = duration_units(pause, Duration::from_millis(100), 0..=255)?;
+                if self.has_buzzer() {
+                    out.extend_from_slice(consts::BUZZER_PATTERN);
+                    out.extend_from_slice(&[pattern.value(), c, t]);
+                }
+            }
+            Command::ExternalBuzzer {
+                pattern,
+                times,
+                on,
+                off,
+            } => {
+                let c = buzzer_count(times)?;
+                let unit = Duration::from_millis(100);
+                let t1 = duration_units(on, unit, 1..=255)?;
+                let t2 = duration_units(off, unit, 0..=255)?;
+                out.extend_from_slice(consts::BUZZER_EXTERNAL);
+                out.extend_from_slice(&[pattern.value(), c, t1, t2]);
+            }
+            Command::DefineMacro(ref commands) => self.define_macro(commands, out)?,
+            Command::RunMacro { times, wait, mode } => {
+                let t = duration_units(wait, Duration::from_millis(100), 0..=255)?;
+                let m = match mode {
+                    MacroMode::Continuous => 0,
+                    MacroMode::FeedButton => 1,
+                };
+                out.extend_from_slice(consts::MACRO_RUN);
+                out.extend_from_slice(&[times, t, m]);
+                // The macro may have changed any mode
+                self.state = TextStyle::new();
+            }
+            Command::Cut { mode, feed } => {
+                if !self.profile.cutter {
+                    return Err(unsupported(&self.profile, "Cutter"));
+                }
+                let command = match (mode, feed) {
+                    (CutMode::Full, None) => consts::PAPER_FULL_CUT,
+                    (CutMode::Partial, None) => consts::PAPER_PART_CUT,
+                    (CutMode::Full, Some(_)) => consts::PAPER_CUT_A,
+                    (CutMode::Partial, Some(_)) => consts::PAPER_CUT_B,
+                    (CutMode::ReservedFull, _) => consts::PAPER_RESERVE_FULL_CUT,
+                    (CutMode::ReservedPartial, _) => consts::PAPER_RESERVE_PART_CUT,
+                    (CutMode::ReturnFull, _) => consts::PAPER_RETURN_FULL_CUT,
+                    (CutMode::ReturnPartial, _) => consts::PAPER_RETURN_PART_CUT,
+                };
+                out.extend_from_slice(command);
+                if !matches!((mode, feed), (CutMode::Full | CutMode::Partial, None)) {
+                    out.push(feed.unwrap_or(0));
+                }
+            }
+            Command::PageMode => out.extend_from_slice(consts::PAGE_MODE),
+            Command::StandardMode => out.extend_from_slice(consts::STANDARD_MODE),
+            Command::PageArea(area) => {
+                self.check_page_area(area)?;
+                self.page_area = Some(area);
+                out.extend_from_slice(consts::PAGE_AREA);
+                for value in [area.x, area.y, area.width, area.height] {
+                    push_u16le(out, value);
+                }
+            }
+            Command::PageDirection(direction) => {
+                self.page_direction = direction;
+                out.extend_from_slice(consts::PAGE_DIRECTION);
+                out.push(direction.value());
+            }
+            Command::PagePosition { x, y } => {
+                self.check_page_position(x, y)?;
+                out.extend_from_slice(consts::POS_ABSOLUTE);
+                push_u16le(out, x);
+                out.extend_from_slice(consts::PAGE_VERTICAL);
+                push_u16le(out, y);
+            }
+            Command::PrintPage => out.extend_from_slice(consts::PAGE_PRINT),
+            Command::CancelPage => out.extend_from_slice(consts::PAGE_CANCEL),
+        }
+        Ok(())
+    }
+}
+
+impl Default for Encoder {
+    fn default() -> Encoder {
+        Encoder::new(None, None)
+    }
+}
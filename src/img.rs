@@ -1,9 +1,14 @@
+#[cfg(any(feature = "serde", feature = "html"))]
+use std::io;
 use std::iter::Iterator;
 use std::path;
 
 use image;
 use image::{error::ImageResult, DynamicImage, GenericImageView};
+#[cfg(feature = "serde")]
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct Image {
     pub width: u32,
     pub height: u32,
@@ -63,6 +68,50 @@ impl Image {
         })
     }
 
+    /// Image of `width` x `height` dots from `GS v 0` raster data, rows of
+    /// 8 dots per byte with the leftmost dot in the high bit
+    pub fn from_raster(width: u32, height: u32, data: &[u8]) -> Image {
+        let row = (width + 7) / 8;
+        Image::from_dots(width, height, |x, y| {
+            let byte = data.get((y * row + x / 8) as usize).copied().unwrap_or(0);
+            byte & (0x80 >> (x % 8)) != 0
+        })
+    }
+
+    /// Image `width` dots wide from `ESC *` column data, `dots` (8 or 24)
+    /// dots high with the top dot in the high bit
+    pub fn from_columns(width: u32, dots: u32, data: &[u8]) -> Image {
+        let column = dots / 8;
+        Image::from_dots(width, dots, |x, y| {
+            let byte = data
+                .get((x * column + y / 8) as usize)
+                .copied()
+                .unwrap_or(0);
+            byte & (0x80 >> (y % 8)) != 0
+        })
+    }
+
+    fn from_dots<F: Fn(u32, u32) -> bool>(width: u32, height: u32, dot: F) -> Image {
+        let img_buf = image::RgbImage::from_fn(width, height, |x, y| {
+            if dot(x, y) {
+                image::Rgb([0, 0, 0])
+            } else {
+                image::Rgb([255, 255, 255])
+            }
+        });
+        Image::from(DynamicImage::ImageRgb8(img_buf))
+    }
+
+    /// The image as a base64 encoded PNG
+    #[cfg(any(feature = "serde", feature = "html"))]
+    pub(crate) fn to_base64_png(&self) -> ImageResult<String> {
+        use base64::Engine;
+
+        let mut png = io::Cursor::new(vec![]);
+        self.img_buf.write_to(&mut png, image::ImageFormat::Png)?;
+        Ok(base64::engine::general_purpose::STANDARD.encode(png.into_inner()))
+    }
+
     pub fn is_blank_pixel(&self, x: u32, y: u32) -> bool {
         let pixel = self.img_buf.get_pixel(x, y);
         // full transprant OR is white
@@ -120,6 +169,29 @@ impl Image {
     }
 }
 
+/// Images serialize as a base64 encoded PNG
+#[cfg(feature = "serde")]
+impl Serialize for Image {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let encoded = self.to_base64_png().map_err(ser::Error::custom)?;
+        serializer.serialize_str(&encoded)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Image {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Image, D::Error> {
+        use base64::Engine;
+
+        let encoded = String::deserialize(deserializer)?;
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .map_err(de::Error::custom)?;
+        let img_buf = image::load_from_memory(&data).map_err(de::Error::custom)?;
+        Ok(Image::from(img_buf))
+    }
+}
+
 pub struct BitimageLines<'a> {
     line: u32,
     density: u32,
@@ -0,0 +1,72 @@
+//! Printer identification (`GS I`, `GS ( E`)
+//!
+//! Responses to the identification queries are either a single byte (model,
+//! type and ROM version IDs) or a text block framed as `"_" data NUL`.
+
+use crate::consts;
+
+/// Everything a printer reported about itself through `GS I`.
+///
+/// Fields are `None` when the printer did not answer the corresponding query,
+/// which is common on older or non-Epson models.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PrinterInfo {
+    pub model_id: Option<u8>,
+    pub type_id: Option<u8>,
+    pub rom_version: Option<u8>,
+    pub firmware: Option<String>,
+    pub manufacturer: Option<String>,
+    pub model_name: Option<String>,
+    pub serial_number: Option<String>,
+    pub font_of_language: Option<String>,
+}
+
+impl PrinterInfo {
+    /// Two-byte character codes are supported (type ID bit 0)
+    pub fn supports_multibyte(&self) -> bool {
+        self.type_id.map_or(false, |id| id & 0x01 != 0)
+    }
+
+    /// An autocutter is installed (type ID bit 1)
+    pub fn has_autocutter(&self) -> bool {
+        self.type_id.map_or(false, |id| id & 0x02 != 0)
+    }
+
+    /// A customer display is connected (type ID bit 2)
+    pub fn has_customer_display(&self) -> bool {
+        self.type_id.map_or(false, |id| id & 0x04 != 0)
+    }
+}
+
+/// Parse a `GS I` text block (`"_" data NUL`) into its data.
+///
+/// Returns `None` if the block is not framed by the `_` header.
+/// A missing trailing NUL is tolerated.
+pub fn parse_info_block(block: &[u8]) -> Option<String> {
+    let (&header, rest) = block.split_first()?;
+    if header != consts::INFO_BLOCK_HEADER {
+        return None;
+    }
+    let data = match rest.iter().position(|&b| b == consts::INFO_TERMINATOR) {
+        Some(end) => &rest[..end],
+        None => rest,
+    };
+    Some(String::from_utf8_lossy(data).trim().to_string())
+}
+
+/// Parse a `GS ( E` customized value response
+/// (`0x37 identifier a 0x1f value NUL`) into `(a, value)`.
+pub fn parse_custom_value(block: &[u8]) -> Option<(u8, u32)> {
+    if block.len() < 2 || block[0] != consts::INFO_CUSTOM_HEADER {
+        return None;
+    }
+    let end = block
+        .iter()
+        .position(|&b| b == consts::INFO_TERMINATOR)
+        .unwrap_or(block.len());
+    let body = block.get(2..end)?;
+    let sep = body.iter().position(|&b| b == consts::INFO_SEPARATOR)?;
+    let number = std::str::from_utf8(&body[..sep]).ok()?.parse().ok()?;
+    let value = std::str::from_utf8(&body[sep + 1..]).ok()?.parse().ok()?;
+    Some((number, value))
+}
@@ -0,0 +1,371 @@
+//! Receipt layout: word wrapping, columns, justified lines and tables
+//!
+//! Widths are counted in character cells, CJK and other wide characters
+//! take two cells. The `Printer` methods lay out to
+//! [Printer::columns][crate::printer::Printer::columns] of the current
+//! font and size, the free functions return the lines for any width.
+//!
+//! ```rust
+//! use std::io;
+//! use escposify::layout::{Column, Table};
+//! use escposify::printer::Printer;
+//! use escposify::style::Alignment;
+//!
+//! fn main() -> io::Result<()> {
+//!     let mut printer = Printer::new(io::sink(), None, None);
+//!     let table = Table::new(vec![
+//!         Column::new(0),
+//!         Column::new(3).align(Alignment::Right),
+//!         Column::new(8).align(Alignment::Right),
+//!     ])
+//!     .header(&["Item", "Qty", "Price"])
+//!     .row(&["Espresso", "2", "5.00"])
+//!     .row(&["Blueberry muffin with extra blueberries", "1", "3.50"]);
+//!     printer.table(&table)?;
+//!     printer.justify("Total", "8.50", '.')?;
+//!     printer.flush()
+//! }
+//! ```
+
+use std::io;
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::printer::Printer;
+use crate::style::Alignment;
+
+/// What to do with cell content wider than its column
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    /// Continue on the following lines
+    Wrap,
+    /// Cut off at the column width
+    Truncate,
+}
+
+/// A table or row column
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Column {
+    /// Width in character cells, 0 shares the remaining width with the
+    /// other 0 width columns
+    pub width: usize,
+    pub align: Alignment,
+    pub overflow: Overflow,
+}
+
+impl Column {
+    pub fn new(width: usize) -> Column {
+        Column {
+            width,
+            align: Alignment::Left,
+            overflow: Overflow::Wrap,
+        }
+    }
+
+    pub fn align(mut self, align: Alignment) -> Column {
+        self.align = align;
+        self
+    }
+
+    pub fn truncate(mut self) -> Column {
+        self.overflow = Overflow::Truncate;
+        self
+    }
+}
+
+/// Rows of cells laid out in columns, optionally below a header and a
+/// separator line
+#[derive(Clone, Debug)]
+pub struct Table {
+    columns: Vec<Column>,
+    header: Option<Vec<String>>,
+    rows: Vec<Vec<String>>,
+    gap: usize,
+    separator: char,
+}
+
+impl Table {
+    /// Columns are separated by a single space
+    pub fn new(columns: Vec<Column>) -> Table {
+        Table {
+            columns,
+            header: None,
+            rows: vec![],
+            gap: 1,
+            separator: '-',
+        }
+    }
+
+    pub fn header(mut self, cells: &[&str]) -> Table {
+        self.header = Some(cells.iter().map(|cell| cell.to_string()).collect());
+        self
+    }
+
+    pub fn row(mut self, cells: &[&str]) -> Table {
+        self.rows
+            .push(cells.iter().map(|cell| cell.to_string()).collect());
+        self
+    }
+
+    /// Spaces between columns
+    pub fn gap(mut self, gap: usize) -> Table {
+        self.gap = gap;
+        self
+    }
+
+    /// Character of the line below the header
+    pub fn separator(mut self, c: char) -> Table {
+        self.separator = c;
+        self
+    }
+
+    /// The lines of the table at `width` cells
+    pub fn lines(&self, width: usize) -> Vec<String> {
+        let mut lines = vec![];
+        if let Some(ref header) = self.header {
+            let cells: Vec<&str> = header.iter().map(|cell| cell.as_str()).collect();
+            lines.extend(row(&self.columns, &cells, self.gap, width));
+            lines.push(separator(self.separator, width));
+        }
+        for cells in &self.rows {
+            let cells: Vec<&str> = cells.iter().map(|cell| cell.as_str()).collect();
+            lines.extend(row(&self.columns, &cells, self.gap, width));
+        }
+        lines
+    }
+}
+
+/// Display width of `text` in character cells, the sum of its characters'
+/// widths as the printer prints them one by one
+pub fn text_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+fn char_width(c: char) -> usize {
+    UnicodeWidthChar::width(c).unwrap_or(0)
+}
+
+/// Cut `text` to at most `width` cells
+pub fn truncate(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (i, c) in text.char_indices() {
+        used += char_width(c);
+        if used > width {
+            return &text[..i];
+        }
+    }
+    text
+}
+
+/// Pad `text` with spaces to `width` cells, truncating it if longer
+pub fn pad(text: &str, width: usize, align: Alignment) -> String {
+    let text = truncate(text, width);
+    let space = width.saturating_sub(text_width(text));
+    let (left, right) = match align {
+        Alignment::Left => (0, space),
+        Alignment::Center => (space / 2, space - space / 2),
+        Alignment::Right => (space, 0),
+    };
+    format!("{}{}{}", " ".repeat(left), text, " ".repeat(right))
+}
+
+/// Word wrap `text` at `width` cells. Wide characters may be broken
+/// between, words longer than a line are broken anywhere.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = vec![];
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        let mut line_width = 0;
+        let mut continued = false;
+        for word in words(paragraph) {
+            let word_width = text_width(word);
+            let is_space = word.trim().is_empty();
+            if is_space && continued && line.is_empty() {
+                continue;
+            }
+            if line_width + word_width <= width {
+                line.push_str(word);
+                line_width += word_width;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(line.trim_end().to_string());
+                line.clear();
+                line_width = 0;
+                continued = true;
+            }
+            if is_space {
+                continue;
+            }
+            for c in word.chars() {
+                let w = char_width(c);
+                if line_width + w > width && !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                }
+                line.push(c);
+                line_width += w;
+            }
+        }
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
+
+/// Split into runs of whitespace, words and single wide characters
+fn words(text: &str) -> Vec<&str> {
+    let mut words = vec![];
+    let mut start = 0;
+    let mut prev: Option<(bool, bool)> = None;
+    for (i, c) in text.char_indices() {
+        let kind = (c.is_whitespace(), char_width(c) > 1);
+        if let Some(prev) = prev {
+            if prev != kind || kind.1 {
+                words.push(&text[start..i]);
+                start = i;
+            }
+        }
+        prev = Some(kind);
+    }
+    if start < text.len() {
+        words.push(&text[start..]);
+    }
+    words
+}
+
+/// Lay out one row of `cells` in `columns` separated by `gap` spaces,
+/// wrapped cells continue on the following lines
+pub fn row(columns: &[Column], cells: &[&str], gap: usize, width: usize) -> Vec<String> {
+    let widths = column_widths(columns, gap, width);
+    let cells: Vec<Vec<String>> = columns
+        .iter()
+        .zip(&widths)
+        .enumerate()
+        .map(|(i, (column, &width))| {
+            let cell = cells.get(i).copied().unwrap_or("");
+            match column.overflow {
+                Overflow::Wrap => wrap(cell, width),
+                Overflow::Truncate => vec![truncate(cell, width).to_string()],
+            }
+        })
+        .collect();
+    let height = cells.iter().map(|lines| lines.len()).max().unwrap_or(1);
+    (0..height)
+        .map(|line| {
+            let parts: Vec<String> = columns
+                .iter()
+                .zip(&widths)
+                .zip(&cells)
+                .map(|((column, &width), lines)| {
+                    pad(
+                        lines.get(line).map_or("", |s| s.as_str()),
+                        width,
+                        column.align,
+                    )
+                })
+                .collect();
+            parts.join(&" ".repeat(gap)).trim_end().to_string()
+        })
+        .collect()
+}
+
+fn column_widths(columns: &[Column], gap: usize, width: usize) -> Vec<usize> {
+    let fixed: usize = columns.iter().map(|column| column.width).sum();
+    let gaps = gap * columns.len().saturating_sub(1);
+    let flexible = columns.iter().filter(|column| column.width == 0).count();
+    let remaining = width.saturating_sub(fixed + gaps);
+    let mut extra = if flexible > 0 {
+        remaining % flexible
+    } else {
+        0
+    };
+    columns
+        .iter()
+        .map(|column| {
+            if column.width > 0 {
+                return column.width;
+            }
+            let mut width = remaining / flexible;
+            if extra > 0 {
+                width += 1;
+                extra -= 1;
+            }
+            width.max(1)
+        })
+        .collect()
+}
+
+/// `left` and `right` at both ends of a `width` cells line with `fill`
+/// in between, e.g. "Total ........ 12.50"
+pub fn justify(left: &str, right: &str, fill: char, width: usize) -> String {
+    let right_width = text_width(right);
+    let left = truncate(left, width.saturating_sub(right_width + 1));
+    let space = width.saturating_sub(text_width(left) + right_width);
+    let filler = if fill == ' ' || space < 3 {
+        " ".repeat(space)
+    } else {
+        format!(" {} ", fill.to_string().repeat(space - 2))
+    };
+    format!("{}{}{}", left, filler, right)
+}
+
+/// A line of `c` across `width` cells
+pub fn separator(c: char, width: usize) -> String {
+    c.to_string().repeat(width / char_width(c).max(1))
+}
+
+impl<W: io::Write> Printer<W> {
+    fn print_lines(&mut self, lines: Vec<String>) -> io::Result<usize> {
+        let mut n = 0;
+        for line in lines {
+            n += self.println(&line)?;
+        }
+        Ok(n)
+    }
+
+    pub fn chain_paragraph(&mut self, text: &str) -> io::Result<&mut Self> {
+        self.paragraph(text).map(|_| self)
+    }
+    /// Print `text` word wrapped at the line width
+    pub fn paragraph(&mut self, text: &str) -> io::Result<usize> {
+        let lines = wrap(text, self.columns() as usize);
+        self.print_lines(lines)
+    }
+
+    pub fn chain_row(&mut self, columns: &[Column], cells: &[&str]) -> io::Result<&mut Self> {
+        self.row(columns, cells).map(|_| self)
+    }
+    /// Print `cells` in `columns` separated by a space
+    pub fn row(&mut self, columns: &[Column], cells: &[&str]) -> io::Result<usize> {
+        let lines = row(columns, cells, 1, self.columns() as usize);
+        self.print_lines(lines)
+    }
+
+    pub fn chain_justify(&mut self, left: &str, right: &str, fill: char) -> io::Result<&mut Self> {
+        self.justify(left, right, fill).map(|_| self)
+    }
+    /// Print `left` and `right` at both ends of the line with `fill` in
+    /// between
+    pub fn justify(&mut self, left: &str, right: &str, fill: char) -> io::Result<usize> {
+        let line = justify(left, right, fill, self.columns() as usize);
+        self.println(&line)
+    }
+
+    pub fn chain_separator(&mut self, c: char) -> io::Result<&mut Self> {
+        self.separator(c).map(|_| self)
+    }
+    /// Print a line of `c` across the paper
+    pub fn separator(&mut self, c: char) -> io::Result<usize> {
+        let line = separator(c, self.columns() as usize);
+        self.println(&line)
+    }
+
+    pub fn chain_table(&mut self, table: &Table) -> io::Result<&mut Self> {
+        self.table(table).map(|_| self)
+    }
+    pub fn table(&mut self, table: &Table) -> io::Result<usize> {
+        let lines = table.lines(self.columns() as usize);
+        self.print_lines(lines)
+    }
+}
@@ -9,7 +9,7 @@
 //! use std::io;
 //!
 //! use escposify::device::File;
-//! use escposify::printer::Printer;
+//! use escposify::printer::{CutMode, Printer};
 //!
 //! use tempfile::NamedTempFileOptions;
 //!
@@ -28,7 +28,7 @@
 //!         .chain_text("敏捷的棕色狐狸跳过懒狗")?
 //!         .chain_barcode("12345678", "EAN8", "", "", 0, 0)?
 //!         .chain_feed(1)?
-//!         .chain_cut(false)?
+//!         .chain_cut(CutMode::Full, Some(0))?
 //!         .flush()
 //! }
 //! ```
@@ -40,7 +40,7 @@
 //! use std::fs::File;
 //! use std::io;
 //!
-//! use escposify::printer::Printer;
+//! use escposify::printer::{CutMode, Printer};
 //!
 //! fn main() -> io::Result<()> {
 //!     let device_file = File::options().append(true).open("/dev/usb/lp0").unwrap();
@@ -52,7 +52,7 @@
 //!         .chain_size(0,0)?
 //!         .chain_text("The quick brown fox jumps over the lazy dog")?
 //!         .chain_feed(1)?
-//!         .chain_cut(false)?
+//!         .chain_cut(CutMode::Full, Some(0))?
 //!         .flush()
 //! }
 //! ```
@@ -62,6 +62,8 @@
 //! Understandably not all options work here (alignment, fonts, chain_cut etc.)
 //! but for quick debugging and prototyping this is a good option
 //! as it saves tons of time when working on the logic of your implementation.
+//! For a preview with alignment, sizes and cuts, render the output as text
+//! with [render::text] instead.
 //!
 //! ```rust
 //! use std::io::{self, stdout};
@@ -80,11 +82,31 @@
 //! }
 //! ```
 //!
+//! ```rust
+//! use std::io;
+//! use escposify::printer::{CutMode, Printer};
+//! use escposify::profile::Profile;
+//! use escposify::{decoder, render};
+//!
+//! fn main() -> io::Result<()> {
+//!     let mut output = vec![];
+//!     Printer::new(&mut output, None, None)
+//!         .chain_align("ct")?
+//!         .chain_text("The quick brown fox jumps over the lazy dog")?
+//!         .chain_cut(CutMode::Full, None)?
+//!         .flush()?;
+//!
+//!     let document = decoder::document(&output);
+//!     print!("{}", render::text(&document, &Profile::default()));
+//!     Ok(())
+//! }
+//! ```
+//!
 //! ### Printing to a printer via USB
 //!
 //! ```no_run
 //! use std::io;
-//! use escposify::printer::Printer;
+//! use escposify::printer::{CutMode, Printer};
 //! use escposify::device::Usb;
 //!
 //! fn main() -> io::Result<()> {
@@ -103,12 +125,32 @@
 //!         .chain_text("The quick brown fox jumps over the lazy dog")?
 //!         .chain_barcode("12345678", "EAN8", "", "", 0, 0)?
 //!         .chain_feed(5)?
-//!         .chain_cut(false)?
+//!         .chain_cut(CutMode::Full, Some(0))?
 //!         .flush()
 //! }
 //! ```
 
+#[cfg(any(feature = "emulator", feature = "html"))]
+mod barcode;
+#[cfg(feature = "serde")]
+pub mod call;
+pub mod codepage;
+pub mod command;
 pub mod consts;
+pub mod decoder;
 pub mod device;
+#[cfg(feature = "emulator")]
+pub mod emulator;
+pub mod encoder;
 pub mod img;
+pub mod info;
+pub mod layout;
+pub mod page;
 pub mod printer;
+pub mod profile;
+pub mod render;
+pub mod simulator;
+pub mod style;
+pub mod translit;
+#[cfg(feature = "truetype")]
+pub mod ttf;
@@ -0,0 +1,186 @@
+//! Page mode: lay out text, barcodes and images freely on a page before
+//! printing it at once
+//!
+//! In page mode the printer buffers everything in a print area, which can
+//! be rotated with a [Direction], and prints it with
+//! [Page::print_page] or when [Printer::page_mode] returns.
+//!
+//! ```rust
+//! use std::io;
+//! use escposify::page::{Area, Direction};
+//! use escposify::printer::Printer;
+//!
+//! fn main() -> io::Result<()> {
+//!     let mut printer = Printer::new(io::sink(), None, None);
+//!     let area = Area::new(0, 0, 512, 400);
+//!     printer.page_mode(area, Direction::BottomToTop, |page| {
+//!         page.move_to(0, 30)?;
+//!         page.println("Rotated label")
+//!     })?;
+//!     printer.flush()
+//! }
+//! ```
+
+use std::io;
+use std::ops::{Deref, DerefMut};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::command::{Command, Control};
+use crate::printer::Printer;
+
+/// Print direction and starting corner of the page (`ESC T`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Direction {
+    /// Left to right, starting at the upper left
+    LeftToRight,
+    /// Bottom to top, starting at the lower left
+    BottomToTop,
+    /// Right to left, starting at the lower right
+    RightToLeft,
+    /// Top to bottom, starting at the upper right
+    TopToBottom,
+}
+
+impl Direction {
+    pub(crate) fn value(self) -> u8 {
+        match self {
+            Direction::LeftToRight => 0,
+            Direction::BottomToTop => 1,
+            Direction::RightToLeft => 2,
+            Direction::TopToBottom => 3,
+        }
+    }
+
+    /// Whether lines run along the paper feed instead of across it
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Direction::BottomToTop | Direction::TopToBottom)
+    }
+}
+
+/// Print area of the page in dots (`ESC W`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Area {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Area {
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Area {
+        Area {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+/// A printer in page mode, see [Printer::page_mode]
+///
+/// Dereferences to the [Printer] for printing text, barcodes and images
+/// into the page.
+pub struct Page<'a, W: io::Write> {
+    printer: &'a mut Printer<W>,
+    area: Area,
+    direction: Direction,
+}
+
+impl<W: io::Write> Page<'_, W> {
+    pub fn area(&self) -> Area {
+        self.area
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn chain_set_area(&mut self, area: Area) -> io::Result<&mut Self> {
+        self.set_area(area).map(|_| self)
+    }
+    /// Change the print area, positions are relative to it
+    pub fn set_area(&mut self, area: Area) -> io::Result<usize> {
+        let n = self.printer.command(Command::PageArea(area))?;
+        self.area = area;
+        Ok(n)
+    }
+
+    pub fn chain_set_direction(&mut self, direction: Direction) -> io::Result<&mut Self> {
+        self.set_direction(direction).map(|_| self)
+    }
+    pub fn set_direction(&mut self, direction: Direction) -> io::Result<usize> {
+        self.direction = direction;
+        self.printer.command(Command::PageDirection(direction))
+    }
+
+    pub fn chain_move_to(&mut self, x: u16, y: u16) -> io::Result<&mut Self> {
+        self.move_to(x, y).map(|_| self)
+    }
+    /// Move to `x` dots along and `y` dots across the print direction from
+    /// the starting corner of the area (`ESC $`, `GS $`)
+    pub fn move_to(&mut self, x: u16, y: u16) -> io::Result<usize> {
+        self.printer.command(Command::PagePosition { x, y })
+    }
+
+    pub fn chain_print_page(&mut self) -> io::Result<&mut Self> {
+        self.print_page().map(|_| self)
+    }
+    /// Print the page and stay in page mode, the page content is kept
+    /// (`ESC FF`)
+    pub fn print_page(&mut self) -> io::Result<usize> {
+        self.printer.command(Command::PrintPage)
+    }
+}
+
+impl<W: io::Write> Deref for Page<'_, W> {
+    type Target = Printer<W>;
+
+    fn deref(&self) -> &Printer<W> {
+        self.printer
+    }
+}
+
+impl<W: io::Write> DerefMut for Page<'_, W> {
+    fn deref_mut(&mut self) -> &mut Printer<W> {
+        self.printer
+    }
+}
+
+impl<W: io::Write> Printer<W> {
+    /// Enter page mode with `area` and `direction`, run `f` and print the
+    /// page returning to standard mode (`FF`). If `f` fails, the page is
+    /// discarded (`CAN`) and the printer returned to standard mode
+    /// (`ESC S`) before returning the error.
+    pub fn page_mode<T, F>(&mut self, area: Area, direction: Direction, f: F) -> io::Result<T>
+    where
+        F: FnOnce(&mut Page<W>) -> io::Result<T>,
+    {
+        self.command(Command::PageMode)?;
+        let mut page = Page {
+            printer: self,
+            area,
+            direction,
+        };
+        let result = page
+            .set_area(area)
+            .and_then(|_| page.set_direction(direction))
+            .and_then(|_| f(&mut page));
+        match result {
+            Ok(value) => {
+                self.command(Command::Control(Control::Ff))?;
+                Ok(value)
+            }
+            Err(err) => {
+                self.command(Command::CancelPage)?;
+                self.command(Command::StandardMode)?;
+                Err(err)
+            }
+        }
+    }
+}
@@ -1,11 +1,20 @@
 use std::io::{self, Write};
+use std::time::Duration;
 
-use byteorder::{LittleEndian, WriteBytesExt};
-use encoding::all::UTF_8;
 use encoding::types::{EncoderTrap, EncodingRef};
 
+use crate::command::{
+    BitImageDensity, Command, Control, Document, DrawerPin, KanjiEncoding, RasterMode,
+};
+pub use crate::command::{BuzzerPattern, CutMode, MacroMode};
 use crate::consts;
+pub use crate::encoder::MACRO_BUFFER_SIZE;
+use crate::encoder::{self, Encoder};
 use crate::img::Image;
+use crate::info::{parse_custom_value, parse_info_block, PrinterInfo};
+use crate::profile::Profile;
+use crate::style::{Alignment, Font, TextStyle};
+use crate::translit::Transliterator;
 
 /// Allows for printing to a [device][crate::device]
 ///
@@ -29,74 +38,136 @@ use crate::img::Image;
 /// ```
 pub struct Printer<W: io::Write> {
     writer: io::BufWriter<W>,
-    codec: EncodingRef,
-    trap: EncoderTrap,
+    encoder: Encoder,
+    /// Bytes recorded by [Printer::define_macro] instead of written
+    recording: Option<Vec<u8>>,
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
 }
 
 impl<W: io::Write> Printer<W> {
     pub fn new(writer: W, codec: Option<EncodingRef>, trap: Option<EncoderTrap>) -> Printer<W> {
         Printer {
             writer: io::BufWriter::new(writer),
-            codec: codec.unwrap_or(UTF_8 as EncodingRef),
-            trap: trap.unwrap_or(EncoderTrap::Replace),
+            encoder: Encoder::new(codec, trap),
+            recording: None,
         }
     }
 
-    fn encode(&mut self, content: &str) -> io::Result<Vec<u8>> {
-        self.codec
-            .encode(content, self.trap)
-            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+    /// Capability profile of the printer, [Profile::default] unless set
+    pub fn profile(&self) -> &Profile {
+        self.encoder.profile()
+    }
+
+    /// Use the capabilities of `profile`, e.g. one picked with
+    /// [Profile::from_info]. Commands the profile does not support are
+    /// rejected with [io::ErrorKind::Unsupported] or emulated.
+    pub fn set_profile(&mut self, profile: Profile) {
+        self.encoder.set_profile(profile)
+    }
+
+    /// Encode every character of printed text with the best code page of
+    /// the profile, sending `ESC t` only when the code page has to change.
+    ///
+    /// When disabled, text is encoded with the code page selected by
+    /// [code_page][Printer::code_page] or with the codec otherwise.
+    pub fn set_auto_code_page(&mut self, enabled: bool) {
+        self.encoder.set_auto_code_page(enabled)
+    }
+
+    /// Replace characters the current encoding can't represent before
+    /// printing them, e.g. with [Transliterator::default]. Disabled (`None`)
+    /// by default, unmappable characters are then handled by the trap.
+    pub fn set_transliterator(&mut self, transliterator: Option<Transliterator>) {
+        self.encoder.set_transliterator(transliterator)
+    }
+
+    /// Encoder turning the commands of this printer into bytes
+    pub fn encoder(&self) -> &Encoder {
+        &self.encoder
     }
 
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.writer.write(buf)
+        match self.recording {
+            Some(ref mut recording) => recording.extend_from_slice(buf),
+            None => self.writer.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn send(&mut self, command: &Command) -> io::Result<usize> {
+        if self.recording.is_some() && matches!(command, Command::DefineMacro(_)) {
+            return Err(encoder::nested_macro());
+        }
+        let bytes = self.encoder.encode(command)?;
+        self.write(&bytes)
+    }
+
+    pub fn chain_command(&mut self, command: Command) -> io::Result<&mut Self> {
+        self.command(command).map(|_| self)
+    }
+    /// Send `command`, returns the number of bytes written
+    pub fn command(&mut self, command: Command) -> io::Result<usize> {
+        self.send(&command)
+    }
+
+    pub fn chain_print_document(&mut self, document: &Document) -> io::Result<&mut Self> {
+        self.print_document(document).map(|_| self)
+    }
+    /// Send every command of `document`
+    pub fn print_document(&mut self, document: &Document) -> io::Result<usize> {
+        let mut n = 0;
+        for command in document {
+            n += self.send(command)?;
+        }
+        Ok(n)
     }
 
     pub fn chain_write_u8(&mut self, n: u8) -> io::Result<&mut Self> {
         self.write_u8(n).map(|_| self)
     }
     pub fn write_u8(&mut self, n: u8) -> io::Result<usize> {
-        self.write(vec![n].as_slice())
-    }
-
-    fn write_u16le(&mut self, n: u16) -> io::Result<usize> {
-        let mut wtr = vec![];
-        wtr.write_u16::<LittleEndian>(n)?;
-        self.write(wtr.as_slice())
+        self.command(Command::Raw(vec![n]))
     }
 
     pub fn flush(&mut self) -> io::Result<()> {
         self.writer.flush()
     }
 
+    /// Current text state, `None` for values that are unknown because they
+    /// were never set since the printer was created
+    pub fn state(&self) -> &TextStyle {
+        self.encoder.state()
+    }
+
     pub fn chain_hwinit(&mut self) -> io::Result<&mut Self> {
         self.hwinit().map(|_| self)
     }
     pub fn hwinit(&mut self) -> io::Result<usize> {
-        self.write(consts::HW_INIT)
+        self.command(Command::Init)
     }
 
     pub fn chain_hwselect(&mut self) -> io::Result<&mut Self> {
         self.hwselect().map(|_| self)
     }
     pub fn hwselect(&mut self) -> io::Result<usize> {
-        self.write(consts::HW_SELECT)
+        self.command(Command::Select)
     }
 
     pub fn chain_hwreset(&mut self) -> io::Result<&mut Self> {
         self.hwreset().map(|_| self)
     }
     pub fn hwreset(&mut self) -> io::Result<usize> {
-        self.write(consts::HW_RESET)
+        self.command(Command::Reset)
     }
 
     pub fn chain_print(&mut self, content: &str) -> io::Result<&mut Self> {
         self.print(content).map(|_| self)
     }
     pub fn print(&mut self, content: &str) -> io::Result<usize> {
-        // let rv = self.encode(content);
-        let rv = self.encode(content)?;
-        self.write(rv.as_slice())
+        self.command(Command::Text(content.to_string()))
     }
 
     pub fn chain_println(&mut self, content: &str) -> io::Result<&mut Self> {
@@ -113,130 +184,483 @@ impl<W: io::Write> Printer<W> {
         self.println(content)
     }
 
+    pub fn chain_code_page(&mut self, n: u8) -> io::Result<&mut Self> {
+        self.code_page(n).map(|_| self)
+    }
+    /// Select character code table `n` (`ESC t n`) from the profile's
+    /// code page table, text is encoded with it from now on
+    pub fn code_page(&mut self, n: u8) -> io::Result<usize> {
+        self.command(Command::CodePage(n))
+    }
+
+    pub fn chain_set_codec(&mut self, codec: EncodingRef) -> io::Result<&mut Self> {
+        self.set_codec(codec).map(|_| self)
+    }
+    /// Encode text with `codec` from now on. If the profile has a code page
+    /// for the codec it is selected with `ESC t`.
+    pub fn set_codec(&mut self, codec: EncodingRef) -> io::Result<usize> {
+        self.command(Command::Codec(codec.name().to_string()))
+    }
+
+    pub fn chain_set_charset(&mut self, charset: &str) -> io::Result<&mut Self> {
+        self.set_charset(charset).map(|_| self)
+    }
+    /// Select the international character set (`ESC R n`) used for
+    /// `#$@[\]^{|}~`
+    pub fn set_charset(&mut self, charset: &str) -> io::Result<usize> {
+        self.command(Command::charset(charset)?)
+    }
+
+    /// Print with `codec` and, if given, the international character set
+    /// `charset` inside `f`, switching back to the previous ones afterwards
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::io;
+    /// use encoding::all::WINDOWS_1252;
+    /// use escposify::printer::Printer;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let mut printer = Printer::new(io::sink(), None, None);
+    ///     printer.with_codec(WINDOWS_1252, Some("GERMANY"), |p| p.text("Grüße"))?;
+    ///     printer.flush()
+    /// }
+    /// ```
+    pub fn with_codec<F>(
+        &mut self,
+        codec: EncodingRef,
+        charset: Option<&str>,
+        f: F,
+    ) -> io::Result<usize>
+    where
+        F: FnOnce(&mut Self) -> io::Result<usize>,
+    {
+        let (prev_codec, prev_code_page, prev_charset) = (
+            self.encoder.codec,
+            self.encoder.code_page,
+            self.encoder.charset,
+        );
+        let mut n = self.set_codec(codec)?;
+        if let Some(charset) = charset {
+            n += self.set_charset(charset)?;
+        }
+        let rv = f(self);
+
+        self.encoder.codec = prev_codec;
+        n += match prev_code_page {
+            Some(page) if self.encoder.code_page != prev_code_page => self.code_page(page)?,
+            Some(_) => 0,
+            None => {
+                // ESC @ selects code page 0
+                let reset = match self.encoder.code_page {
+                    Some(page) if page != 0 => {
+                        self.command(Command::Raw([consts::CODE_PAGE, &[0]].concat()))?
+                    }
+                    _ => 0,
+                };
+                self.encoder.code_page = None;
+                reset
+            }
+        };
+        if charset.is_some() {
+            // ESC @ selects the USA character set
+            n += self.command(Command::Charset(prev_charset.unwrap_or(0)))?;
+        }
+        Ok(n + rv?)
+    }
+
+    pub fn chain_kanji_encoding(&mut self, encoding: &str) -> io::Result<&mut Self> {
+        self.kanji_encoding(encoding).map(|_| self)
+    }
+    /// Select the multi-byte encoding used by [print_cjk][Printer::print_cjk]
+    /// (`GB2312` by default).
+    ///
+    /// `JIS` and `SJIS` are selected on the printer with `FS C`, the Chinese
+    /// and Korean encodings are fixed by the printer model.
+    pub fn kanji_encoding(&mut self, encoding: &str) -> io::Result<usize> {
+        self.command(Command::KanjiEncoding(KanjiEncoding::parse(encoding)?))
+    }
+
+    pub fn chain_print_cjk(&mut self, content: &str) -> io::Result<&mut Self> {
+        self.print_cjk(content).map(|_| self)
+    }
+    /// Print text containing Chinese, Japanese or Korean characters.
+    ///
+    /// Runs of characters missing from the current code page are printed in
+    /// Kanji mode (`FS &` ... `FS .`) with the encoding selected by
+    /// [kanji_encoding][Printer::kanji_encoding], everything else as usual.
+    pub fn print_cjk(&mut self, content: &str) -> io::Result<usize> {
+        self.command(Command::TextCjk(content.to_string()))
+    }
+
+    pub fn chain_text_cjk(&mut self, content: &str) -> io::Result<&mut Self> {
+        self.text_cjk(content).map(|_| self)
+    }
+    pub fn text_cjk(&mut self, content: &str) -> io::Result<usize> {
+        self.print_cjk(format!("{}{}", content, consts::EOL).as_ref())
+    }
+
+    pub fn chain_kanji_mode(
+        &mut self,
+        double_width: bool,
+        double_height: bool,
+        underline: bool,
+    ) -> io::Result<&mut Self> {
+        self.kanji_mode(double_width, double_height, underline)
+            .map(|_| self)
+    }
+    /// Select print modes for Kanji characters (`FS !`)
+    pub fn kanji_mode(
+        &mut self,
+        double_width: bool,
+        double_height: bool,
+        underline: bool,
+    ) -> io::Result<usize> {
+        self.command(Command::KanjiMode {
+            double_width,
+            double_height,
+            underline,
+        })
+    }
+
+    pub fn chain_kanji_underline(&mut self, n: u8) -> io::Result<&mut Self> {
+        self.kanji_underline(n).map(|_| self)
+    }
+    /// Kanji underline off (0), 1-dot (1) or 2-dot (2) thick (`FS -`)
+    pub fn kanji_underline(&mut self, n: u8) -> io::Result<usize> {
+        self.command(Command::KanjiUnderline(n))
+    }
+
+    pub fn chain_kanji_quadruple(&mut self, enabled: bool) -> io::Result<&mut Self> {
+        self.kanji_quadruple(enabled).map(|_| self)
+    }
+    /// Kanji quadruple-size mode (`FS W`)
+    pub fn kanji_quadruple(&mut self, enabled: bool) -> io::Result<usize> {
+        self.command(Command::KanjiQuadruple(enabled))
+    }
+
     pub fn chain_line_space(&mut self, n: i32) -> io::Result<&mut Self> {
         self.line_space(n).map(|_| self)
     }
     pub fn line_space(&mut self, n: i32) -> io::Result<usize> {
-        if n >= 0 {
-            Ok(self.write(consts::LS_SET)? + self.write_u8(n as u8)?)
+        self.command(Command::LineSpace(if n >= 0 {
+            Some(n as u8)
         } else {
-            self.write(consts::LS_DEFAULT)
+            None
+        }))
+    }
+
+    pub fn chain_set_tab_stops(&mut self, stops: &[u8]) -> io::Result<&mut Self> {
+        self.set_tab_stops(stops).map(|_| self)
+    }
+    /// Set the horizontal tab positions (`ESC D`) in characters from the
+    /// start of the line. At most 32 ascending positions, an empty slice
+    /// clears them.
+    pub fn set_tab_stops(&mut self, stops: &[u8]) -> io::Result<usize> {
+        self.command(Command::TabStops(stops.to_vec()))
+    }
+
+    pub fn chain_tab_row(&mut self, cells: &[&str]) -> io::Result<&mut Self> {
+        self.tab_row(cells).map(|_| self)
+    }
+    /// Print a line of `cells` separated by horizontal tabs, aligned by
+    /// the printer at the positions set with [Printer::set_tab_stops]
+    pub fn tab_row(&mut self, cells: &[&str]) -> io::Result<usize> {
+        let mut n = 0;
+        for (i, cell) in cells.iter().enumerate() {
+            if i > 0 {
+                n += self.command(Command::Control(Control::Ht))?;
+            }
+            n += self.print(cell)?;
         }
+        Ok(n + self.print(consts::EOL)?)
+    }
+
+    /// Convert millimetres to dots at the resolution of the profile
+    pub fn mm_to_dots(&self, mm: f32) -> u16 {
+        (mm * self.profile().dpi as f32 / 25.4).round() as u16
+    }
+
+    pub fn chain_left_margin(&mut self, dots: u16) -> io::Result<&mut Self> {
+        self.left_margin(dots).map(|_| self)
+    }
+    /// Set the left margin in dots (`GS L`), effective at the start of a
+    /// line
+    pub fn left_margin(&mut self, dots: u16) -> io::Result<usize> {
+        self.command(Command::LeftMargin(dots))
+    }
+
+    pub fn chain_print_area_width(&mut self, dots: u16) -> io::Result<&mut Self> {
+        self.print_area_width(dots).map(|_| self)
+    }
+    /// Set the printing area width in dots (`GS W`), effective at the start
+    /// of a line
+    pub fn print_area_width(&mut self, dots: u16) -> io::Result<usize> {
+        self.command(Command::PrintAreaWidth(dots))
+    }
+
+    pub fn chain_position(&mut self, dots: u16) -> io::Result<&mut Self> {
+        self.position(dots).map(|_| self)
+    }
+    /// Move to `dots` from the start of the line (`ESC $`)
+    pub fn position(&mut self, dots: u16) -> io::Result<usize> {
+        self.command(Command::Position(dots))
+    }
+
+    pub fn chain_relative_position(&mut self, dots: i16) -> io::Result<&mut Self> {
+        self.relative_position(dots).map(|_| self)
+    }
+    /// Move `dots` right, or left if negative, of the current position
+    /// (`ESC \`)
+    pub fn relative_position(&mut self, dots: i16) -> io::Result<usize> {
+        self.command(Command::RelativePosition(dots))
     }
 
     pub fn chain_feed(&mut self, n: usize) -> io::Result<&mut Self> {
         self.feed(n).map(|_| self)
     }
+    /// Print and feed `n` lines with newlines, nothing if `n` is 0
     pub fn feed(&mut self, n: usize) -> io::Result<usize> {
-        let n = if n < 1 { 1 } else { n };
-        self.write(consts::EOL.repeat(n).as_ref())
+        self.command(Command::Feed(n))
+    }
+
+    pub fn chain_feed_dots(&mut self, n: u8) -> io::Result<&mut Self> {
+        self.feed_dots(n).map(|_| self)
+    }
+    /// Print and feed `n` motion units (`ESC J`), independent of the line
+    /// spacing
+    pub fn feed_dots(&mut self, n: u8) -> io::Result<usize> {
+        self.command(Command::FeedDots(n))
+    }
+
+    pub fn chain_feed_lines(&mut self, n: u8) -> io::Result<&mut Self> {
+        self.feed_lines(n).map(|_| self)
+    }
+    /// Print and feed `n` lines of the current line spacing (`ESC d`)
+    pub fn feed_lines(&mut self, n: u8) -> io::Result<usize> {
+        self.command(Command::FeedLines(n))
+    }
+
+    pub fn chain_reverse_feed_dots(&mut self, n: u8) -> io::Result<&mut Self> {
+        self.reverse_feed_dots(n).map(|_| self)
+    }
+    /// Print and feed back `n` motion units (`ESC K`)
+    pub fn reverse_feed_dots(&mut self, n: u8) -> io::Result<usize> {
+        self.command(Command::ReverseFeedDots(n))
+    }
+
+    pub fn chain_reverse_feed_lines(&mut self, n: u8) -> io::Result<&mut Self> {
+        self.reverse_feed_lines(n).map(|_| self)
+    }
+    /// Print and feed back `n` lines (`ESC e`)
+    pub fn reverse_feed_lines(&mut self, n: u8) -> io::Result<usize> {
+        self.command(Command::ReverseFeedLines(n))
+    }
+
+    pub fn chain_feed_to_cut(&mut self) -> io::Result<&mut Self> {
+        self.feed_to_cut().map(|_| self)
+    }
+    /// Feed the paper to the cutting position (`FS ( L`)
+    pub fn feed_to_cut(&mut self) -> io::Result<usize> {
+        self.command(Command::FeedToCut)
     }
 
     pub fn chain_control(&mut self, ctrl: &str) -> io::Result<&mut Self> {
         self.control(ctrl).map(|_| self)
     }
     pub fn control(&mut self, ctrl: &str) -> io::Result<usize> {
-        let ctrl_upper = ctrl.to_uppercase();
-        let ctrl_value = match ctrl_upper.as_ref() {
-            "LF" => consts::CTL_LF,
-            "FF" => consts::CTL_FF,
-            "CR" => consts::CTL_CR,
-            "HT" => consts::CTL_HT,
-            "VT" => consts::CTL_VT,
-            _ => {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!("Invalid control action: {}", ctrl),
-                ))
-            }
-        };
-        self.write(ctrl_value)
+        self.command(Command::Control(Control::parse(ctrl)?))
     }
 
     pub fn chain_align(&mut self, alignment: &str) -> io::Result<&mut Self> {
         self.align(alignment).map(|_| self)
     }
     pub fn align(&mut self, alignment: &str) -> io::Result<usize> {
-        let align_upper = alignment.to_uppercase();
-        let align_value = match align_upper.as_ref() {
-            "LT" => consts::TXT_ALIGN_LT,
-            "CT" => consts::TXT_ALIGN_CT,
-            "RT" => consts::TXT_ALIGN_RT,
-            _ => {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!("Invalid alignment: {}", alignment),
-                ))
-            }
-        };
-        self.write(align_value)
+        self.command(Command::Align(Alignment::parse(alignment)?))
     }
 
     pub fn chain_font(&mut self, family: &str) -> io::Result<&mut Self> {
         self.font(family).map(|_| self)
     }
     pub fn font(&mut self, family: &str) -> io::Result<usize> {
-        let family_upper = family.to_uppercase();
-        let family_value = match family_upper.as_ref() {
-            "A" => consts::TXT_FONT_A,
-            "B" => consts::TXT_FONT_B,
-            "C" => consts::TXT_FONT_C,
-            _ => {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!("Invalid font family: {}", family),
-                ))
-            }
-        };
-        self.write(family_value)
+        self.command(Command::Font(Font::parse(family)?))
     }
 
     pub fn chain_style(&mut self, kind: &str) -> io::Result<&mut Self> {
         self.style(kind).map(|_| self)
     }
     pub fn style(&mut self, kind: &str) -> io::Result<usize> {
-        let kind_upper = kind.to_uppercase();
-        match kind_upper.as_ref() {
-            "B" => Ok(self.write(consts::TXT_UNDERL_OFF)? + self.write(consts::TXT_BOLD_ON)?),
-            "U" => Ok(self.write(consts::TXT_BOLD_OFF)? + self.write(consts::TXT_UNDERL_ON)?),
-            "U2" => Ok(self.write(consts::TXT_BOLD_OFF)? + self.write(consts::TXT_UNDERL2_ON)?),
-            "BU" => Ok(self.write(consts::TXT_BOLD_ON)? + self.write(consts::TXT_UNDERL_ON)?),
-            "BU2" => Ok(self.write(consts::TXT_BOLD_ON)? + self.write(consts::TXT_UNDERL2_ON)?),
-            // "NORMAL" | _ =>
-            _ => Ok(self.write(consts::TXT_BOLD_OFF)? + self.write(consts::TXT_UNDERL_OFF)?),
+        let [bold, underline] = Command::style(kind);
+        Ok(self.command(bold)? + self.command(underline)?)
+    }
+
+    pub fn chain_bold(&mut self, enabled: bool) -> io::Result<&mut Self> {
+        self.bold(enabled).map(|_| self)
+    }
+    pub fn bold(&mut self, enabled: bool) -> io::Result<usize> {
+        self.command(Command::Bold(enabled))
+    }
+
+    pub fn chain_underline(&mut self, n: u8) -> io::Result<&mut Self> {
+        self.underline(n).map(|_| self)
+    }
+    /// Underline off (0), 1-dot (1) or 2-dot (2) thick
+    pub fn underline(&mut self, n: u8) -> io::Result<usize> {
+        self.command(Command::Underline(n))
+    }
+
+    pub fn chain_reverse(&mut self, enabled: bool) -> io::Result<&mut Self> {
+        self.reverse(enabled).map(|_| self)
+    }
+    /// White/black reverse printing (`GS B`)
+    pub fn reverse(&mut self, enabled: bool) -> io::Result<usize> {
+        self.command(Command::Reverse(enabled))
+    }
+
+    pub fn chain_upside_down(&mut self, enabled: bool) -> io::Result<&mut Self> {
+        self.upside_down(enabled).map(|_| self)
+    }
+    /// Upside-down printing (`ESC {`), applies to whole lines
+    pub fn upside_down(&mut self, enabled: bool) -> io::Result<usize> {
+        self.command(Command::UpsideDown(enabled))
+    }
+
+    pub fn chain_rotate(&mut self, enabled: bool) -> io::Result<&mut Self> {
+        self.rotate(enabled).map(|_| self)
+    }
+    /// 90 degree clockwise rotation (`ESC V`)
+    pub fn rotate(&mut self, enabled: bool) -> io::Result<usize> {
+        self.command(Command::Rotate(enabled))
+    }
+
+    pub fn chain_double_strike(&mut self, enabled: bool) -> io::Result<&mut Self> {
+        self.double_strike(enabled).map(|_| self)
+    }
+    /// Double-strike printing (`ESC G`)
+    pub fn double_strike(&mut self, enabled: bool) -> io::Result<usize> {
+        self.command(Command::DoubleStrike(enabled))
+    }
+
+    pub fn chain_smoothing(&mut self, enabled: bool) -> io::Result<&mut Self> {
+        self.smoothing(enabled).map(|_| self)
+    }
+    /// Smoothing of enlarged characters (`GS b`)
+    pub fn smoothing(&mut self, enabled: bool) -> io::Result<usize> {
+        self.command(Command::Smoothing(enabled))
+    }
+
+    pub fn chain_char_spacing(&mut self, n: u8) -> io::Result<&mut Self> {
+        self.char_spacing(n).map(|_| self)
+    }
+    /// Right-side character spacing in dots (`ESC SP`)
+    pub fn char_spacing(&mut self, n: u8) -> io::Result<usize> {
+        self.command(Command::CharSpacing(n))
+    }
+
+    pub fn chain_second_color(&mut self, enabled: bool) -> io::Result<&mut Self> {
+        self.second_color(enabled).map(|_| self)
+    }
+    /// Print with the second color of two-color printers (`ESC r`)
+    pub fn second_color(&mut self, enabled: bool) -> io::Result<usize> {
+        self.command(Command::SecondColor(enabled))
+    }
+
+    pub fn chain_apply_style(&mut self, style: &TextStyle) -> io::Result<&mut Self> {
+        self.apply_style(style).map(|_| self)
+    }
+    /// Change the attributes set in `style`
+    pub fn apply_style(&mut self, style: &TextStyle) -> io::Result<usize> {
+        let commands = [
+            style.font.map(Command::Font),
+            style.align.map(Command::Align),
+            style
+                .size
+                .map(|(width, height)| Command::Size { width, height }),
+            style.bold.map(Command::Bold),
+            style.underline.map(Command::Underline),
+            style.reverse.map(Command::Reverse),
+            style.upside_down.map(Command::UpsideDown),
+            style.rotate.map(Command::Rotate),
+            style.double_strike.map(Command::DoubleStrike),
+            style.smoothing.map(Command::Smoothing),
+            style.spacing.map(Command::CharSpacing),
+            style.second_color.map(Command::SecondColor),
+        ];
+        let mut n = 0;
+        for command in commands.into_iter().flatten() {
+            n += self.command(command)?;
         }
+        Ok(n)
+    }
+
+    pub fn chain_reset_style(&mut self) -> io::Result<&mut Self> {
+        self.reset_style().map(|_| self)
+    }
+    /// Reset every text attribute, font, size, alignment and line spacing
+    /// to its default without clearing the print buffer like `ESC @`
+    pub fn reset_style(&mut self) -> io::Result<usize> {
+        Ok(self.apply_style(&TextStyle::plain())? + self.line_space(-1)?)
+    }
+
+    /// Print with `style` inside `f`, restoring the previous text state
+    /// afterwards. Attributes already active are not sent again.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::io;
+    /// use escposify::printer::Printer;
+    /// use escposify::style::TextStyle;
+    ///
+    /// fn main() -> io::Result<()> {
+    ///     let mut printer = Printer::new(io::sink(), None, None);
+    ///     printer.text("Subtotal 9.00")?;
+    ///     printer.with_style(&TextStyle::new().bold(true).size(2, 2), |p| {
+    ///         p.text("TOTAL 12.50")
+    ///     })?;
+    ///     printer.flush()
+    /// }
+    /// ```
+    pub fn with_style<F>(&mut self, style: &TextStyle, f: F) -> io::Result<usize>
+    where
+        F: FnOnce(&mut Self) -> io::Result<usize>,
+    {
+        let previous = *self.state();
+        let mut n = self.apply_style(style)?;
+        let rv = f(self);
+        let restore = previous.restore(self.state());
+        n += self.apply_style(&restore)?;
+        Ok(n + rv?)
     }
 
     pub fn chain_size(&mut self, width: usize, height: usize) -> io::Result<&mut Self> {
         self.size(width, height).map(|_| self)
     }
+    /// Magnify characters 1-8 times in width and height (`GS !`),
+    /// 0 is treated as 1 (normal size)
     pub fn size(&mut self, width: usize, height: usize) -> io::Result<usize> {
-        let mut n = self.write(consts::TXT_NORMAL)?;
-        if width == 2 {
-            n += self.write(consts::TXT_2WIDTH)?;
-        }
-        if height == 2 {
-            n += self.write(consts::TXT_2HEIGHT)?;
-        }
-        Ok(n)
+        // Out of range sizes are rejected by the encoder
+        self.command(Command::Size {
+            width: u8::try_from(width).unwrap_or(u8::MAX),
+            height: u8::try_from(height).unwrap_or(u8::MAX),
+        })
+    }
+
+    /// Characters per line with the current font and character width
+    pub fn columns(&self) -> u32 {
+        self.encoder.columns()
     }
 
     pub fn chain_hardware(&mut self, hw: &str) -> io::Result<&mut Self> {
         self.hardware(hw).map(|_| self)
     }
     pub fn hardware(&mut self, hw: &str) -> io::Result<usize> {
-        let value = match hw {
-            "INIT" => consts::HW_INIT,
-            "SELECT" => consts::HW_SELECT,
-            "RESET" => consts::HW_RESET,
-            _ => {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!("Invalid hardware command: {}", hw),
-                ))
-            }
+        let command = match hw {
+            "INIT" => Command::Init,
+            "SELECT" => Command::Select,
+            "RESET" => Command::Reset,
+            _ => return Err(invalid(format!("Invalid hardware command: {}", hw))),
         };
-        self.write(value)
+        self.command(command)
     }
 
     pub fn chain_barcode(
@@ -251,6 +675,7 @@ impl<W: io::Write> Printer<W> {
         self.barcode(code, kind, position, font, width, height)
             .map(|_| self)
     }
+    /// Print a barcode, see [Command::barcode] for the arguments
     pub fn barcode(
         &mut self,
         code: &str,
@@ -260,44 +685,7 @@ impl<W: io::Write> Printer<W> {
         width: usize,
         height: usize,
     ) -> io::Result<usize> {
-        let mut n = 0;
-        if width >= 1 || width <= 255 {
-            n += self.write(consts::BARCODE_WIDTH)?;
-        }
-        if height >= 2 || height <= 6 {
-            n += self.write(consts::BARCODE_HEIGHT)?;
-        }
-
-        let font = font.to_uppercase();
-        let position = position.to_uppercase();
-        let kind = kind.to_uppercase().replace('-', "_");
-        let font_value = match font.as_ref() {
-            "B" => consts::BARCODE_FONT_B,
-            // "A" | _ =>
-            _ => consts::BARCODE_FONT_A,
-        };
-        let txt_value = match position.as_ref() {
-            "OFF" => consts::BARCODE_TXT_OFF,
-            "ABV" => consts::BARCODE_TXT_ABV,
-            "BTH" => consts::BARCODE_TXT_BTH,
-            // "BLW" | _ =>
-            _ => consts::BARCODE_TXT_BLW,
-        };
-        let kind_value = match kind.as_ref() {
-            "UPC_A" => consts::BARCODE_UPC_A,
-            "UPC_E" => consts::BARCODE_UPC_E,
-            "EAN8" => consts::BARCODE_EAN8,
-            "CODE39" => consts::BARCODE_CODE39,
-            "ITF" => consts::BARCODE_ITF,
-            "NW7" => consts::BARCODE_NW7,
-            // "EAN13" | _ =>
-            _ => consts::BARCODE_EAN13,
-        };
-        n += self.write(font_value)?;
-        self.write(txt_value)?;
-        self.write(kind_value)?;
-        self.write(code.as_bytes())?;
-        Ok(n)
+        self.command(Command::barcode(code, kind, position, font, width, height))
     }
 
     #[cfg(feature = "qrcode")]
@@ -327,51 +715,181 @@ impl<W: io::Write> Printer<W> {
         level: &str,
         size: Option<i32>,
     ) -> io::Result<usize> {
-        let level = level.to_uppercase();
-        let level_value = match level.as_ref() {
-            "M" => consts::QR_LEVEL_M,
-            "Q" => consts::QR_LEVEL_Q,
-            "H" => consts::QR_LEVEL_H,
-            // "L" | _ =>
-            _ => consts::QR_LEVEL_L,
+        use crate::command::QrLevel;
+
+        let byte = |what: &str, value: i32| {
+            u8::try_from(value).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid QR code {}: {}", what, value),
+                )
+            })
         };
-        let mut n = 0;
-        n += self.write(consts::TYPE_QR)?;
-        n += self.write(consts::CODE2D)?;
-        n += self.write_u8(version.unwrap_or(3) as u8)?;
-        n += self.write(level_value)?;
-        n += self.write_u8(size.unwrap_or(3) as u8)?;
-        n += self.write_u16le(code.len() as u16)?;
-        n += self.write(code.as_bytes())?;
-        Ok(n)
+        self.command(Command::QrCode {
+            code: code.to_string(),
+            version: byte("version", version.unwrap_or(3))?,
+            level: QrLevel::from_name(level),
+            size: byte("size", size.unwrap_or(3))?,
+        })
     }
 
     pub fn chain_cashdraw(&mut self, pin: i32) -> io::Result<&mut Self> {
         self.cashdraw(pin).map(|_| self)
     }
+    /// Pulse drawer kick-out connector pin 2 or 5 for 50 ms, then wait
+    /// 500 ms (`ESC p`)
     pub fn cashdraw(&mut self, pin: i32) -> io::Result<usize> {
-        let pin_value = if pin == 5 {
-            consts::CD_KICK_5
-        } else {
-            consts::CD_KICK_2
-        };
-        self.write(pin_value)
+        self.command(Command::CashDraw(DrawerPin::new(pin)?))
+    }
+
+    pub fn chain_drawer_pulse(
+        &mut self,
+        pin: i32,
+        on_ms: u16,
+        off_ms: u16,
+    ) -> io::Result<&mut Self> {
+        self.drawer_pulse(pin, on_ms, off_ms).map(|_| self)
+    }
+    /// Pulse drawer kick-out connector pin 2 or 5 for `on_ms`, then wait
+    /// `off_ms` (`ESC p`). Times are rounded down to 2 ms, up to 510 ms.
+    pub fn drawer_pulse(&mut self, pin: i32, on_ms: u16, off_ms: u16) -> io::Result<usize> {
+        self.command(Command::DrawerPulse {
+            pin: DrawerPin::new(pin)?,
+            on_ms,
+            off_ms,
+        })
     }
 
-    pub fn chain_cut(&mut self, part: bool) -> io::Result<&mut Self> {
-        self.cut(part).map(|_| self)
+    pub fn chain_drawer_pulse_realtime(&mut self, pin: i32, on_ms: u16) -> io::Result<&mut Self> {
+        self.drawer_pulse_realtime(pin, on_ms).map(|_| self)
+    }
+    /// Pulse drawer kick-out connector pin 2 or 5 for `on_ms` (100 to 800,
+    /// in steps of 100 ms) in real time (`DLE DC4`), processed even while
+    /// the printer is busy or offline
+    pub fn drawer_pulse_realtime(&mut self, pin: i32, on_ms: u16) -> io::Result<usize> {
+        self.command(Command::DrawerPulseRealtime {
+            pin: DrawerPin::new(pin)?,
+            on_ms,
+        })
     }
 
-    pub fn cut(&mut self, part: bool) -> io::Result<usize> {
-        let mut n_bytes = 0;
-        n_bytes += self.print(consts::EOL.repeat(3).as_ref())?;
-        let paper_cut_type = if part {
-            consts::PAPER_PART_CUT
-        } else {
-            consts::PAPER_FULL_CUT
-        };
-        n_bytes += self.write(paper_cut_type)?;
-        Ok(n_bytes)
+    pub fn chain_beep(&mut self, times: u8, duration: Duration) -> io::Result<&mut Self> {
+        self.beep(times, duration).map(|_| self)
+    }
+    /// Beep 1 to 9 `times` for 50 to 450 ms each (`ESC B`). Does nothing if
+    /// the profile has no buzzer.
+    pub fn beep(&mut self, times: u8, duration: Duration) -> io::Result<usize> {
+        self.command(Command::Beep { times, duration })
+    }
+
+    pub fn chain_buzzer(
+        &mut self,
+        pattern: BuzzerPattern,
+        times: u8,
+        pause: Duration,
+    ) -> io::Result<&mut Self> {
+        self.buzzer(pattern, times, pause).map(|_| self)
+    }
+    /// Sound `pattern` 1 to 63 `times` with `pause` between them, up to 25.5
+    /// seconds in steps of 100 ms (`ESC ( A`). Does nothing if the profile
+    /// has no buzzer.
+    pub fn buzzer(
+        &mut self,
+        pattern: BuzzerPattern,
+        times: u8,
+        pause: Duration,
+    ) -> io::Result<usize> {
+        self.command(Command::Buzzer {
+            pattern,
+            times,
+            pause,
+        })
+    }
+
+    pub fn chain_external_buzzer(
+        &mut self,
+        pattern: BuzzerPattern,
+        times: u8,
+        on: Duration,
+        off: Duration,
+    ) -> io::Result<&mut Self> {
+        self.external_buzzer(pattern, times, on, off).map(|_| self)
+    }
+    /// Sound `pattern` on the external buzzer 1 to 63 `times`, `on` and
+    /// `off` up to 25.5 seconds in steps of 100 ms (`ESC ( A`). The
+    /// external buzzer is connected to the drawer kick-out connector, so
+    /// it does not depend on the profile.
+    pub fn external_buzzer(
+        &mut self,
+        pattern: BuzzerPattern,
+        times: u8,
+        on: Duration,
+        off: Duration,
+    ) -> io::Result<usize> {
+        self.command(Command::ExternalBuzzer {
+            pattern,
+            times,
+            on,
+            off,
+        })
+    }
+
+    pub fn chain_define_macro<F>(&mut self, f: F) -> io::Result<&mut Self>
+    where
+        F: FnOnce(&mut Self) -> io::Result<usize>,
+    {
+        self.define_macro(f).map(|_| self)
+    }
+    /// Define the commands issued by `f` as the printer's macro (`GS :`),
+    /// replacing the previous one. Nothing is printed until
+    /// [Printer::run_macro], and the tracked text state is left as it was
+    /// before `f`.
+    pub fn define_macro<F>(&mut self, f: F) -> io::Result<usize>
+    where
+        F: FnOnce(&mut Self) -> io::Result<usize>,
+    {
+        if self.recording.is_some() {
+            return Err(encoder::nested_macro());
+        }
+        let snapshot = self.encoder.snapshot();
+        self.recording = Some(vec![]);
+        let result = f(self);
+        let recording = self.recording.take().unwrap_or_default();
+        self.encoder.restore(snapshot);
+        result?;
+        let mut buf = vec![];
+        encoder::wrap_macro(&recording, &mut buf)?;
+        self.write(&buf)
+    }
+
+    pub fn chain_run_macro(
+        &mut self,
+        times: u8,
+        wait: Duration,
+        mode: MacroMode,
+    ) -> io::Result<&mut Self> {
+        self.run_macro(times, wait, mode).map(|_| self)
+    }
+    /// Run the macro `times` times (`GS ^`). With [MacroMode::Continuous]
+    /// the printer waits `wait` (up to 25.5 seconds in steps of 100 ms)
+    /// between runs, with [MacroMode::FeedButton] it waits for the FEED
+    /// button after the LED blinked for `wait`. Afterwards the tracked text
+    /// state is unknown, so the next style changes are always sent.
+    pub fn run_macro(&mut self, times: u8, wait: Duration, mode: MacroMode) -> io::Result<usize> {
+        self.command(Command::RunMacro { times, wait, mode })
+    }
+
+    pub fn chain_cut(&mut self, mode: CutMode, feed: Option<u8>) -> io::Result<&mut Self> {
+        self.cut(mode, feed).map(|_| self)
+    }
+    /// Cut the paper (`GS V`)
+    ///
+    /// With `feed`, [CutMode::Full] and [CutMode::Partial] first feed the
+    /// paper to the cutting position plus `feed` motion units, so the last
+    /// printed line is above the cutter. Without, they cut where the paper
+    /// is.
+    pub fn cut(&mut self, mode: CutMode, feed: Option<u8>) -> io::Result<usize> {
+        self.command(Command::Cut { mode, feed })
     }
 
     pub fn chain_bit_image(
@@ -382,48 +900,131 @@ impl<W: io::Write> Printer<W> {
         self.bit_image(image, density).map(|_| self)
     }
     pub fn bit_image(&mut self, image: &Image, density: Option<&str>) -> io::Result<usize> {
-        let density = density.unwrap_or("d24");
-        let density_upper = density.to_uppercase();
-        let header = match density_upper.as_ref() {
-            "S8" => consts::BITMAP_S8,
-            "D8" => consts::BITMAP_D8,
-            "S24" => consts::BITMAP_S24,
-            // "D24" | _ =>
-            _ => consts::BITMAP_D24,
-        };
-        let n = if density == "s8" || density == "d8" {
-            1
-        } else {
-            3
-        };
-        let mut n_bytes = 0;
-        n_bytes += self.line_space(0)?;
-        for line in image.bitimage_lines(n * 8) {
-            n_bytes += self.write(header)?;
-            n_bytes += self.write_u16le((line.len() / n as usize) as u16)?;
-            n_bytes += self.write(line.as_ref())?;
-            n_bytes += self.feed(1)?;
-        }
-        Ok(n_bytes)
+        self.command(Command::BitImage {
+            image: image.clone(),
+            density: BitImageDensity::from_name(density.unwrap_or("D24")),
+        })
+    }
+
+    pub fn chain_image(&mut self, image: &Image) -> io::Result<&mut Self> {
+        self.image(image).map(|_| self)
+    }
+    /// Print an image with the graphics command preferred by the profile
+    pub fn image(&mut self, image: &Image) -> io::Result<usize> {
+        self.command(Command::Image(image.clone()))
     }
 
     pub fn chain_raster(&mut self, image: &Image, mode: Option<&str>) -> io::Result<&mut Self> {
         self.raster(image, mode).map(|_| self)
     }
     pub fn raster(&mut self, image: &Image, mode: Option<&str>) -> io::Result<usize> {
-        let mode_upper = mode.unwrap_or("NORMAL").to_uppercase();
-        let header = match mode_upper.as_ref() {
-            "DH" => consts::GSV0_DH,
-            "DWDH" => consts::GSV0_DWDH,
-            "DW" => consts::GSV0_DW,
-            // "NORMAL" | _ =>
-            _ => consts::GSV0_NORMAL,
-        };
-        let mut n_bytes = 0;
-        n_bytes += self.write(header)?;
-        n_bytes += self.write_u16le(((image.width + 7) / 8) as u16)?;
-        n_bytes += self.write_u16le(image.height as u16)?;
-        n_bytes += self.write(image.get_raster().as_ref())?;
-        Ok(n_bytes)
+        self.command(Command::Raster {
+            image: image.clone(),
+            mode: RasterMode::from_name(mode.unwrap_or("NORMAL")),
+        })
+    }
+}
+
+/// Queries that need an answer from the printer, only available for devices
+/// that can also be read from (e.g. [Network][crate::device::Network] and
+/// [Usb][crate::device::Usb]).
+///
+/// Reads block until the printer answers, so devices should be configured
+/// with a read timeout.
+impl<W: io::Read + io::Write> Printer<W> {
+    fn read_u8(&mut self) -> io::Result<u8> {
+        let mut buf = [0u8; 1];
+        self.writer.get_mut().read_exact(&mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn read_block(&mut self, header: u8) -> io::Result<Vec<u8>> {
+        // Skip anything (e.g. automatic status bytes) until the block header
+        while self.read_u8()? != header {}
+        let mut block = vec![header];
+        loop {
+            let b = self.read_u8()?;
+            block.push(b);
+            if b == consts::INFO_TERMINATOR {
+                return Ok(block);
+            }
+        }
+    }
+
+    fn query(&mut self, command: &[u8]) -> io::Result<()> {
+        self.writer.write_all(command)?;
+        self.flush()
+    }
+
+    /// Send a one byte `GS I` query (model ID, type ID or ROM version)
+    pub fn transmit_id(&mut self, query: &[u8]) -> io::Result<u8> {
+        self.query(query)?;
+        self.read_u8()
+    }
+
+    /// Send a `GS I` query answered by a `"_" data NUL` text block
+    pub fn transmit_info(&mut self, query: &[u8]) -> io::Result<String> {
+        self.query(query)?;
+        let block = self.read_block(consts::INFO_BLOCK_HEADER)?;
+        parse_info_block(&block)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid printer info block"))
+    }
+
+    /// Read customized setting value `a` via `GS ( E` (e.g. 3 = paper width)
+    pub fn customized_value(&mut self, a: u8) -> io::Result<u32> {
+        self.query(&[consts::INFO_CUSTOM_VALUE, &[a]].concat())?;
+        let block = self.read_block(consts::INFO_CUSTOM_HEADER)?;
+        match parse_custom_value(&block) {
+            Some((number, value)) if number == a => Ok(value),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid customized value response: {:?}", block),
+            )),
+        }
+    }
+
+    /// Whether drawer kick-out connector pin 3 is high, read with the
+    /// `DLE EOT 1` real-time status. Most drawers close the switch on pin 3
+    /// when open; check the wiring of yours.
+    pub fn drawer_open(&mut self) -> io::Result<bool> {
+        self.query(consts::RT_STATUS_PRINTER)?;
+        let status = self.read_u8()?;
+        // Status bytes are 0xx1xx10
+        if status & 0x93 != 0x12 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid printer status: {:#04x}", status),
+            ));
+        }
+        Ok(status & 0x04 != 0)
+    }
+
+    /// Identify the printer by sending every `GS I` query.
+    ///
+    /// Queries the printer does not answer before the device read timeout
+    /// are left as `None`.
+    pub fn printer_info(&mut self) -> io::Result<PrinterInfo> {
+        fn optional<T>(rv: io::Result<T>) -> io::Result<Option<T>> {
+            match rv {
+                Ok(value) => Ok(Some(value)),
+                Err(err) => match err.kind() {
+                    io::ErrorKind::TimedOut
+                    | io::ErrorKind::WouldBlock
+                    | io::ErrorKind::UnexpectedEof => Ok(None),
+                    _ => Err(err),
+                },
+            }
+        }
+
+        Ok(PrinterInfo {
+            model_id: optional(self.transmit_id(consts::INFO_MODEL_ID))?,
+            type_id: optional(self.transmit_id(consts::INFO_TYPE_ID))?,
+            rom_version: optional(self.transmit_id(consts::INFO_ROM_VERSION))?,
+            firmware: optional(self.transmit_info(consts::INFO_FIRMWARE))?,
+            manufacturer: optional(self.transmit_info(consts::INFO_MANUFACTURER))?,
+            model_name: optional(self.transmit_info(consts::INFO_MODEL_NAME))?,
+            serial_number: optional(self.transmit_info(consts::INFO_SERIAL))?,
+            font_of_language: optional(self.transmit_info(consts::INFO_FONT_LANGUAGE))?,
+        })
     }
 }
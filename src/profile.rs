@@ -0,0 +1,134 @@
+//! Printer capability profiles
+//!
+//! Models differ in fonts, paper width, code pages, cutters and supported
+//! barcodes. The bundled profiles are loaded from an embedded JSON database;
+//! custom profiles can be parsed with [Profile::from_json].
+
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::info::PrinterInfo;
+
+const PROFILES: &str = include_str!("profiles.json");
+
+/// Preferred command for printing images
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Graphics {
+    /// `GS v 0`
+    Raster,
+    /// `ESC *`
+    BitImage,
+}
+
+/// Metrics of a printer font
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FontProfile {
+    pub name: String,
+    /// Characters per line at normal size
+    pub columns: u32,
+    /// Character width in dots
+    pub width: u32,
+    /// Character height in dots
+    pub height: u32,
+}
+
+/// Capabilities of a printer model
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub vendor: String,
+    /// Model names reported by `GS I` that this profile applies to
+    #[serde(default)]
+    pub models: Vec<String>,
+    pub dpi: u32,
+    /// Printable width in dots
+    pub dot_width: u32,
+    pub fonts: Vec<FontProfile>,
+    /// Supported barcode types (`UPC_A`, `EAN13`, ...)
+    pub barcodes: Vec<String>,
+    /// Supported 2D code types (`QR`, `PDF417`, ...)
+    pub codes_2d: Vec<String>,
+    /// Code page table: `ESC t` number to encoding label
+    pub code_pages: BTreeMap<u8, String>,
+    pub cutter: bool,
+    /// Supports reverse paper feed (`ESC e`, `ESC K`)
+    #[serde(default)]
+    pub reverse_feed: bool,
+    /// Has a built-in buzzer (`ESC B`, `ESC ( A`)
+    #[serde(default)]
+    pub buzzer: bool,
+    /// Maximum height of the page mode print area in dots, unchecked if
+    /// unknown
+    #[serde(default)]
+    pub page_height: Option<u32>,
+    pub graphics: Graphics,
+}
+
+impl Profile {
+    /// Parse a list of profiles from JSON
+    pub fn from_json(json: &str) -> io::Result<Vec<Profile>> {
+        serde_json::from_str(json).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// All bundled profiles
+    pub fn all() -> Vec<Profile> {
+        bundled().to_vec()
+    }
+
+    /// Find a bundled profile by name (case insensitive)
+    pub fn get(name: &str) -> Option<Profile> {
+        bundled()
+            .iter()
+            .find(|profile| profile.name.eq_ignore_ascii_case(name))
+            .cloned()
+    }
+
+    /// Pick the bundled profile matching the model name reported by the
+    /// printer, falling back to the default profile
+    pub fn from_info(info: &PrinterInfo) -> Profile {
+        info.model_name
+            .as_ref()
+            .and_then(|model_name| {
+                bundled()
+                    .iter()
+                    .find(|profile| {
+                        profile
+                            .models
+                            .iter()
+                            .any(|model| model.eq_ignore_ascii_case(model_name.trim()))
+                    })
+                    .cloned()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn font(&self, name: &str) -> Option<&FontProfile> {
+        self.fonts
+            .iter()
+            .find(|font| font.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn supports_barcode(&self, kind: &str) -> bool {
+        self.barcodes.iter().any(|b| b.eq_ignore_ascii_case(kind))
+    }
+
+    pub fn supports_2d(&self, kind: &str) -> bool {
+        self.codes_2d.iter().any(|c| c.eq_ignore_ascii_case(kind))
+    }
+}
+
+/// The bundled profiles, parsed on first use
+fn bundled() -> &'static [Profile] {
+    static BUNDLED: OnceLock<Vec<Profile>> = OnceLock::new();
+    BUNDLED.get_or_init(|| Profile::from_json(PROFILES).expect("bundled profiles are valid"))
+}
+
+impl Default for Profile {
+    fn default() -> Profile {
+        Profile::get("default").expect("bundled default profile")
+    }
+}
@@ -0,0 +1,501 @@
+//! Render documents as plain text or HTML
+//!
+//! Previews of a receipt without a printer: [text] lays a document out in
+//! the character columns of a profile for terminals and logs, [html] (with
+//! the `html` feature) as an HTML fragment with inline images for email
+//! receipts. Lines break where the printer would break them. Receipts
+//! printed with a [Printer][crate::printer::Printer] can be rendered from
+//! its output with [decoder::document][crate::decoder::document].
+//!
+//! ```rust
+//! use escposify::command::{Command, Document};
+//! use escposify::profile::Profile;
+//! use escposify::render;
+//! use escposify::style::Alignment;
+//!
+//! let document: Document = vec![
+//!     Command::Align(Alignment::Center),
+//!     Command::line("RECEIPT"),
+//! ]
+//! .into();
+//! let text = render::text(&document, &Profile::get("XP-58").unwrap());
+//! assert_eq!(text, "            RECEIPT\n");
+//! ```
+
+use unicode_width::UnicodeWidthChar;
+
+#[cfg(feature = "html")]
+use crate::barcode;
+use crate::command::{
+    BarcodeKind, BarcodeText, BitImageDensity, Command, Control, Document, RasterMode,
+};
+use crate::img::Image;
+use crate::layout;
+use crate::profile::Profile;
+use crate::style::{Alignment, Font};
+
+/// Text attributes of a run of characters
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Style {
+    font: Font,
+    size: (u8, u8),
+    bold: bool,
+    underline: bool,
+    reverse: bool,
+    red: bool,
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style {
+            font: Font::A,
+            size: (1, 1),
+            bold: false,
+            underline: false,
+            reverse: false,
+            red: false,
+        }
+    }
+}
+
+/// Characters of the same style
+struct Run {
+    style: Style,
+    text: String,
+}
+
+/// A printed line, image or code
+#[cfg_attr(not(feature = "html"), allow(dead_code))]
+enum Block {
+    Line {
+        align: Alignment,
+        runs: Vec<Run>,
+    },
+    /// An image printed with `scale` dots per pixel
+    Image {
+        align: Alignment,
+        image: Image,
+        scale: (u32, u32),
+    },
+    Barcode {
+        align: Alignment,
+        code: String,
+        kind: BarcodeKind,
+        text: BarcodeText,
+        font: Font,
+        width: u8,
+        height: u8,
+    },
+    QrCode {
+        align: Alignment,
+        code: String,
+        size: u8,
+    },
+    Cut,
+}
+
+/// Breaks the text of a document into lines like the printer
+struct Layout<'a> {
+    profile: &'a Profile,
+    blocks: Vec<Block>,
+    align: Alignment,
+    style: Style,
+    line: Vec<Run>,
+    /// Width of the line in dots
+    x: u32,
+    macro_commands: Vec<Command>,
+}
+
+impl Layout<'_> {
+    fn new(profile: &Profile) -> Layout<'_> {
+        Layout {
+            profile,
+            blocks: vec![],
+            align: Alignment::Left,
+            style: Style::default(),
+            line: vec![],
+            x: 0,
+            macro_commands: vec![],
+        }
+    }
+
+    fn command(&mut self, command: &Command) {
+        let align = self.align;
+        match *command {
+            Command::Init | Command::Reset => {
+                self.align = Alignment::Left;
+                self.style = Style::default();
+            }
+            Command::Text(ref content) | Command::TextCjk(ref content) => {
+                content.chars().for_each(|c| self.character(c))
+            }
+            Command::Control(Control::Lf) | Command::Control(Control::Ff) => self.end_line(),
+            Command::Control(Control::Ht) => self.character('\t'),
+            Command::Feed(n) => (0..n).for_each(|_| self.end_line()),
+            Command::FeedLines(n) => (0..n).for_each(|_| self.end_line()),
+            Command::FeedDots(_) | Command::FeedToCut if !self.line.is_empty() => self.end_line(),
+            Command::Align(align) => self.align = align,
+            Command::Font(font) => self.style.font = font,
+            Command::Bold(n) | Command::DoubleStrike(n) => self.style.bold = n,
+            Command::Underline(n) => self.style.underline = n > 0,
+            Command::Reverse(n) => self.style.reverse = n,
+            Command::SecondColor(n) => self.style.red = n,
+            Command::Size { width, height } => self.style.size = (width.max(1), height.max(1)),
+            Command::Barcode {
+                ref code,
+                kind,
+                text,
+                font,
+                width,
+                height,
+            } => self.block(Block::Barcode {
+                align,
+                code: code.clone(),
+                kind,
+                text,
+                font,
+                width,
+                height,
+            }),
+            Command::QrCode { ref code, size, .. } => self.block(Block::QrCode {
+                align,
+                code: code.clone(),
+                size,
+            }),
+            Command::Image(ref image) => self.image(image, (1, 1)),
+            Command::Raster { ref image, mode } => {
+                let scale = match mode {
+                    RasterMode::Normal => (1, 1),
+                    RasterMode::DoubleWidth => (2, 1),
+                    RasterMode::DoubleHeight => (1, 2),
+                    RasterMode::Quadruple => (2, 2),
+                };
+                self.image(image, scale)
+            }
+            Command::BitImage { ref image, density } => {
+                let scale = match density {
+                    BitImageDensity::S8 => (2, 3),
+                    BitImageDensity::D8 => (1, 3),
+                    BitImageDensity::S24 => (2, 1),
+                    BitImageDensity::D24 => (1, 1),
+                };
+                self.image(image, scale)
+            }
+            Command::DefineMacro(ref commands) => self.macro_commands = commands.clone(),
+            Command::RunMacro { times, .. } => {
+                let commands = self.macro_commands.clone();
+                for _ in 0..times {
+                    commands
+                        .iter()
+                        .filter(|command| !matches!(command, Command::RunMacro { .. }))
+                        .for_each(|command| self.command(command));
+                }
+            }
+            Command::Cut { .. } => self.block(Block::Cut),
+            _ => {}
+        }
+    }
+
+    /// Width in dots of a character cell of `font`
+    fn cell_width(&self, font: Font) -> u32 {
+        self.profile
+            .font(font.name())
+            .map_or(12, |metrics| metrics.width)
+    }
+
+    fn character(&mut self, c: char) {
+        let style = self.style;
+        let (c, width) = match c {
+            '\n' => return self.end_line(),
+            // Tabs move to the next multiple of 8 columns
+            '\t' => {
+                let column = self.cell_width(style.font) * style.size.0 as u32;
+                let spaces = 8 - self.x / column % 8;
+                (0..spaces).for_each(|_| self.character(' '));
+                return;
+            }
+            c => match c.width() {
+                Some(0) | None => return,
+                Some(width) => {
+                    let font = if width > 1 { Font::A } else { style.font };
+                    (
+                        c,
+                        self.cell_width(font) * width as u32 * style.size.0 as u32,
+                    )
+                }
+            },
+        };
+        if self.x > 0 && self.x + width > self.profile.dot_width {
+            self.end_line();
+        }
+        match self.line.last_mut() {
+            Some(run) if run.style == style => run.text.push(c),
+            _ => self.line.push(Run {
+                style,
+                text: c.to_string(),
+            }),
+        }
+        self.x += width;
+    }
+
+    fn end_line(&mut self) {
+        self.blocks.push(Block::Line {
+            align: self.align,
+            runs: std::mem::take(&mut self.line),
+        });
+        self.x = 0;
+    }
+
+    /// Add `block` below the current line
+    fn block(&mut self, block: Block) {
+        if !self.line.is_empty() {
+            self.end_line();
+        }
+        self.blocks.push(block);
+    }
+
+    fn image(&mut self, image: &Image, scale: (u32, u32)) {
+        self.block(Block::Image {
+            align: self.align,
+            image: image.clone(),
+            scale,
+        });
+    }
+}
+
+fn layout(document: &Document, profile: &Profile) -> Vec<Block> {
+    let mut layout = Layout::new(profile);
+    document.iter().for_each(|command| layout.command(command));
+    if !layout.line.is_empty() {
+        layout.end_line();
+    }
+    layout.blocks
+}
+
+/// Characters per line of font A
+fn columns(profile: &Profile) -> usize {
+    profile
+        .font("A")
+        .or_else(|| profile.fonts.first())
+        .map_or(48, |font| font.columns as usize)
+}
+
+/// Render `document` as fixed width text, `profile` gives the columns
+///
+/// Font A characters take one column, double width ones two. Images and
+/// codes are replaced by a description and cuts by a dashed line.
+pub fn text(document: &Document, profile: &Profile) -> String {
+    let columns = columns(profile);
+    let mut text = String::new();
+    for block in layout(document, profile) {
+        let (align, line) = match block {
+            Block::Line { align, runs } => {
+                let mut line = String::new();
+                for run in runs {
+                    for c in run.text.chars() {
+                        line.push(c);
+                        let spaces = (run.style.size.0 as usize - 1) * c.width().unwrap_or(1);
+                        line.extend(std::iter::repeat(' ').take(spaces));
+                    }
+                }
+                (align, line)
+            }
+            Block::Image {
+                align,
+                image,
+                scale: (sx, sy),
+            } => (
+                align,
+                format!("[image {}x{}]", image.width * sx, image.height * sy),
+            ),
+            Block::Barcode {
+                align, code, kind, ..
+            } => (align, format!("[{} {}]", kind.name(), code)),
+            Block::QrCode { align, code, .. } => (align, format!("[QR code {}]", code)),
+            Block::Cut => (Alignment::Left, "- ".repeat(columns / 2)),
+        };
+        let space = columns.saturating_sub(layout::text_width(&line));
+        let left = match align {
+            Alignment::Left => 0,
+            Alignment::Center => space / 2,
+            Alignment::Right => space,
+        };
+        let line = format!("{}{}", " ".repeat(left), line);
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(feature = "html")]
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(feature = "html")]
+fn css_align(align: Alignment) -> &'static str {
+    match align {
+        Alignment::Left => "left",
+        Alignment::Center => "center",
+        Alignment::Right => "right",
+    }
+}
+
+/// CSS of `style`, sizes relative to font A
+#[cfg(feature = "html")]
+fn css(style: Style) -> String {
+    let mut css = vec![];
+    let font = if style.font == Font::A { 1.0 } else { 0.75 };
+    let (width, height) = (style.size.0 as f32, style.size.1 as f32);
+    if font != 1.0 || height != 1.0 {
+        css.push(format!("font-size: {}em", font * height));
+    }
+    if width != height {
+        css.push(format!(
+            "letter-spacing: {:.2}ch",
+            (width - height) / height
+        ));
+    }
+    if style.bold {
+        css.push("font-weight: bold".to_string());
+    }
+    if style.underline {
+        css.push("text-decoration: underline".to_string());
+    }
+    match (style.reverse, style.red) {
+        (true, true) => css.push("background: #c00; color: #fff".to_string()),
+        (true, false) => css.push("background: #000; color: #fff".to_string()),
+        (false, true) => css.push("color: #c00".to_string()),
+        (false, false) => {}
+    }
+    css.join("; ")
+}
+
+/// `text` in a span with the CSS of `style`
+#[cfg(feature = "html")]
+fn span(style: Style, text: &str) -> String {
+    match css(style) {
+        css if css.is_empty() => escape(text),
+        css => format!("<span style=\"{}\">{}</span>", css, escape(text)),
+    }
+}
+
+/// Black and white `image` with `scale` pixels per pixel, as printed
+#[cfg(feature = "html")]
+fn printed(image: &Image, (sx, sy): (u32, u32)) -> Image {
+    let img_buf = image::RgbImage::from_fn(image.width * sx, image.height * sy, |x, y| {
+        if image.is_blank_pixel(x / sx, y / sy) {
+            image::Rgb([0xff, 0xff, 0xff])
+        } else {
+            image::Rgb([0, 0, 0])
+        }
+    });
+    Image::from(image::DynamicImage::ImageRgb8(img_buf))
+}
+
+/// `img` tag of `image`, `dot_width` dots wide paper is 100%
+#[cfg(feature = "html")]
+fn img(image: &Image, alt: &str, dot_width: u32) -> String {
+    match image.to_base64_png() {
+        Ok(png) => format!(
+            "<img src=\"data:image/png;base64,{}\" alt=\"{}\" style=\"width: {:.2}%; image-rendering: pixelated\">",
+            png,
+            escape(alt),
+            (100.0 * image.width as f32 / dot_width as f32).min(100.0)
+        ),
+        Err(_) => escape(alt),
+    }
+}
+
+/// Render `document` as an HTML fragment for email receipts
+///
+/// Text is monospaced and the receipt as wide as the columns of
+/// `profile`. Images, barcodes and QR codes are inline PNG images, cuts
+/// are dashed lines.
+#[cfg(feature = "html")]
+pub fn html(document: &Document, profile: &Profile) -> String {
+    let dot_width = profile.dot_width;
+    let mut html = format!(
+        "<div style=\"font-family: monospace; white-space: pre; width: {}ch\">\n",
+        columns(profile)
+    );
+    for block in layout(document, profile) {
+        let (align, content) = match block {
+            Block::Line { align, runs } if runs.is_empty() => (align, " ".to_string()),
+            Block::Line { align, runs } => {
+                let content = runs.iter().map(|run| span(run.style, &run.text)).collect();
+                (align, content)
+            }
+            Block::Image {
+                align,
+                image,
+                scale,
+            } => (align, img(&printed(&image, scale), "image", dot_width)),
+            Block::Barcode {
+                align,
+                code,
+                kind,
+                text,
+                font,
+                width,
+                height,
+            } => {
+                let (widths, hri) = match barcode::bars(kind, &code, width.clamp(1, 6) as u32) {
+                    Some(bars) => bars,
+                    None => continue,
+                };
+                let mut bars = vec![];
+                for (i, width) in widths.into_iter().enumerate() {
+                    bars.extend(std::iter::repeat(i % 2 == 0).take(width as usize));
+                }
+                let img_buf =
+                    image::RgbImage::from_fn(bars.len() as u32, height.max(1) as u32, |x, _| {
+                        if bars[x as usize] {
+                            image::Rgb([0, 0, 0])
+                        } else {
+                            image::Rgb([0xff, 0xff, 0xff])
+                        }
+                    });
+                let image = Image::from(image::DynamicImage::ImageRgb8(img_buf));
+                let style = Style {
+                    font,
+                    ..Style::default()
+                };
+                let hri = span(style, &hri);
+                let bars = img(&image, &code, dot_width);
+                let content = match text {
+                    BarcodeText::Off => bars,
+                    BarcodeText::Above => format!("{}\n{}", hri, bars),
+                    BarcodeText::Below => format!("{}\n{}", bars, hri),
+                    BarcodeText::Both => format!("{}\n{}\n{}", hri, bars, hri),
+                };
+                (align, content)
+            }
+            Block::QrCode { align, code, size } => {
+                let modules = match qrcode::QrCode::new(code.as_bytes()) {
+                    Ok(qr) => qr.width() as u32 + 2,
+                    Err(_) => continue,
+                };
+                // Printers support modules of 1 to 16 dots
+                match Image::from_qr(&code, modules * size.clamp(1, 16) as u32) {
+                    Ok(image) => (align, img(&image, &code, dot_width)),
+                    Err(_) => continue,
+                }
+            }
+            Block::Cut => {
+                html.push_str("<hr style=\"border: none; border-top: 1px dashed\">\n");
+                continue;
+            }
+        };
+        html.push_str(&format!(
+            "<div style=\"text-align: {}\">{}</div>\n",
+            css_align(align),
+            content
+        ));
+    }
+    html.push_str("</div>\n");
+    html
+}
@@ -0,0 +1,316 @@
+//! A network printer simulator for integration tests
+//!
+//! The [Simulator] listens on a TCP port like a printer on port 9100 and
+//! keeps the data of every connection as a [Job]. It answers real-time
+//! status (`DLE EOT`), identification (`GS I`) and process ID
+//! (`GS ( H`) queries from a simulated [Status] and [PrinterInfo].
+//! Connections are served one at a time, like a printer does.
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use escposify::device::Network;
+//! use escposify::printer::Printer;
+//! use escposify::simulator::Simulator;
+//!
+//! let simulator = Simulator::start("127.0.0.1:0").unwrap();
+//! let port = simulator.local_addr().port();
+//! {
+//!     let mut printer = Printer::new(Network::new("127.0.0.1", port).unwrap(), None, None);
+//!     printer.chain_println("Hello").unwrap().flush().unwrap();
+//! }
+//! let jobs = simulator.wait_for_jobs(1, Some(Duration::from_secs(5)));
+//! assert_eq!(jobs[0].data, b"Hello\n");
+//! ```
+
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::command::Document;
+use crate::consts;
+use crate::decoder::{self, Decoder, Entry, Item};
+#[cfg(feature = "emulator")]
+use crate::emulator::Emulator;
+use crate::info::PrinterInfo;
+use crate::profile::Profile;
+use crate::render;
+
+/// Simulated printer state, reported by `DLE EOT`
+///
+/// While the printer is offline (paper out, cover open or cutter error)
+/// only real-time queries are answered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Status {
+    pub paper_out: bool,
+    pub paper_near_end: bool,
+    pub cover_open: bool,
+    /// Drawer kick-out connector pin 3 is high
+    pub drawer_open: bool,
+    pub cutter_error: bool,
+}
+
+impl Status {
+    pub fn offline(&self) -> bool {
+        self.paper_out || self.cover_open || self.cutter_error
+    }
+
+    /// Response to `DLE EOT n`
+    pub fn realtime(&self, n: u8) -> Option<u8> {
+        // Status bytes are 0xx1xx10
+        let status = |bits: &[(bool, u8)]| {
+            bits.iter()
+                .filter(|(set, _)| *set)
+                .fold(0x12, |status, (_, bit)| status | bit)
+        };
+        match n {
+            1 => Some(status(&[(self.drawer_open, 0x04), (self.offline(), 0x08)])),
+            2 => Some(status(&[
+                (self.cover_open, 0x04),
+                (self.paper_out, 0x20),
+                (self.cutter_error, 0x40),
+            ])),
+            3 => Some(status(&[(self.cutter_error, 0x08)])),
+            4 => Some(status(&[
+                (self.paper_near_end || self.paper_out, 0x0c),
+                (self.paper_out, 0x60),
+            ])),
+            _ => None,
+        }
+    }
+}
+
+/// Response to `GS I n`, `None` for values the printer doesn't have
+fn info_response(info: &PrinterInfo, n: u8) -> Option<Vec<u8>> {
+    let block = |text: &Option<String>| {
+        text.as_ref().map(|text| {
+            [
+                &[consts::INFO_BLOCK_HEADER],
+                text.as_bytes(),
+                &[consts::INFO_TERMINATOR],
+            ]
+            .concat()
+        })
+    };
+    match n {
+        1 | 49 => info.model_id.map(|id| vec![id]),
+        2 | 50 => info.type_id.map(|id| vec![id]),
+        3 | 51 => info.rom_version.map(|id| vec![id]),
+        0x41 => block(&info.firmware),
+        0x42 => block(&info.manufacturer),
+        0x43 => block(&info.model_name),
+        0x44 => block(&info.serial_number),
+        0x45 => block(&info.font_of_language),
+        _ => None,
+    }
+}
+
+/// The data received on one connection
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Job {
+    pub data: Vec<u8>,
+}
+
+impl Job {
+    pub fn entries(&self) -> Vec<Entry> {
+        decoder::decode(&self.data)
+    }
+
+    pub fn document(&self) -> Document {
+        decoder::document(&self.data)
+    }
+
+    /// The job as text in the columns of `profile`
+    pub fn text(&self, profile: &Profile) -> String {
+        render::text(&self.document(), profile)
+    }
+
+    /// The job printed on paper `dot_width` dots wide
+    #[cfg(feature = "emulator")]
+    pub fn image(&self, dot_width: u32) -> image::RgbImage {
+        let mut emulator = Emulator::new(dot_width);
+        emulator.print(&self.data);
+        emulator.image()
+    }
+}
+
+struct State {
+    status: Status,
+    info: PrinterInfo,
+    jobs: Vec<Job>,
+    stopped: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    job_done: Condvar,
+}
+
+impl Shared {
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Answer to the command `bytes`, if it is a query
+    fn respond(&self, bytes: &[u8]) -> Option<Vec<u8>> {
+        let state = self.state();
+        match *bytes {
+            [0x10, 0x04, n] => state.status.realtime(n).map(|status| vec![status]),
+            _ if state.status.offline() => None,
+            [0x1d, 0x49, n] => info_response(&state.info, n),
+            _ => {
+                let id = bytes.strip_prefix(consts::PROCESS_ID_REQUEST)?;
+                Some([consts::PROCESS_ID_HEADER, id, &[consts::INFO_TERMINATOR]].concat())
+            }
+        }
+    }
+
+    /// Read a job from `stream`, answering queries as they arrive
+    fn serve(&self, mut stream: TcpStream) -> io::Result<()> {
+        let mut decoder = Decoder::default();
+        let mut data = vec![];
+        let mut answered = 0;
+        let mut buf = [0; 4096];
+        let result = loop {
+            let n = match stream.read(&mut buf) {
+                Ok(0) => break Ok(()),
+                Ok(n) => n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => break Err(err),
+            };
+            data.extend_from_slice(&buf[..n]);
+            let pending = &data[answered..];
+            for entry in decoder.decode(pending) {
+                let end = entry.offset + entry.bytes.len();
+                // Wait for the rest of a command split over reads
+                let truncated = matches!(entry.item, Item::Unknown(_))
+                    && end == pending.len()
+                    && matches!(entry.bytes[0], 0x10 | 0x1b | 0x1c | 0x1d);
+                if truncated {
+                    break;
+                }
+                if let Some(response) = self.respond(&entry.bytes) {
+                    stream.write_all(&response)?;
+                }
+                answered += entry.bytes.len();
+            }
+        };
+        if !data.is_empty() {
+            self.state().jobs.push(Job { data });
+            self.job_done.notify_all();
+        }
+        result
+    }
+}
+
+/// A simulated network printer, serving connections in a background
+/// thread until dropped
+pub struct Simulator {
+    addr: SocketAddr,
+    shared: Arc<Shared>,
+}
+
+impl Simulator {
+    /// Listen on `addr`, e.g. `"127.0.0.1:9100"` or port 0 for any free port
+    pub fn start<A: ToSocketAddrs>(addr: A) -> io::Result<Simulator> {
+        let listener = TcpListener::bind(addr)?;
+        let simulator = Simulator {
+            addr: listener.local_addr()?,
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    status: Status::default(),
+                    info: PrinterInfo {
+                        model_id: Some(0x20),
+                        type_id: Some(0x02),
+                        rom_version: Some(0x01),
+                        firmware: Some(env!("CARGO_PKG_VERSION").to_string()),
+                        manufacturer: Some("escposify".to_string()),
+                        model_name: Some("Simulator".to_string()),
+                        serial_number: Some("0".to_string()),
+                        font_of_language: Some("ANK".to_string()),
+                    },
+                    jobs: vec![],
+                    stopped: false,
+                }),
+                job_done: Condvar::new(),
+            }),
+        };
+        let shared = simulator.shared.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if shared.state().stopped {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let _ = shared.serve(stream);
+                }
+            }
+        });
+        Ok(simulator)
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn status(&self) -> Status {
+        self.shared.state().status
+    }
+
+    pub fn set_status(&self, status: Status) {
+        self.shared.state().status = status;
+    }
+
+    pub fn info(&self) -> PrinterInfo {
+        self.shared.state().info.clone()
+    }
+
+    /// Identification reported by `GS I`
+    pub fn set_info(&self, info: PrinterInfo) {
+        self.shared.state().info = info;
+    }
+
+    /// Jobs of the closed connections
+    pub fn jobs(&self) -> Vec<Job> {
+        self.shared.state().jobs.clone()
+    }
+
+    /// Wait until `count` jobs were received or `timeout` passed, `None`
+    /// waits forever
+    pub fn wait_for_jobs(&self, count: usize, timeout: Option<Duration>) -> Vec<Job> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut state = self.shared.state();
+        while state.jobs.len() < count {
+            state = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break;
+                    }
+                    let wait = self.shared.job_done.wait_timeout(state, deadline - now);
+                    wait.unwrap_or_else(|err| err.into_inner()).0
+                }
+                None => {
+                    let wait = self.shared.job_done.wait(state);
+                    wait.unwrap_or_else(|err| err.into_inner())
+                }
+            };
+        }
+        state.jobs.clone()
+    }
+
+    /// Forget the received jobs
+    pub fn clear_jobs(&self) {
+        self.shared.state().jobs.clear();
+    }
+}
+
+impl Drop for Simulator {
+    fn drop(&mut self) {
+        self.shared.state().stopped = true;
+        // Wake up the accept loop
+        let _ = TcpStream::connect(self.addr);
+    }
+}
@@ -0,0 +1,228 @@
+//! Composable text styles
+//!
+//! A [TextStyle] lists the text attributes to change, attributes left as
+//! `None` keep their current value when applied with
+//! [Printer::apply_style][crate::printer::Printer::apply_style].
+//!
+//! ```rust
+//! use escposify::style::TextStyle;
+//!
+//! let total = TextStyle::new().bold(true).reverse(true).spacing(2);
+//! ```
+
+use std::io;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::consts;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Font {
+    A,
+    B,
+    C,
+}
+
+impl Font {
+    /// Parse a font family ("A", "B" or "C")
+    pub fn parse(family: &str) -> io::Result<Font> {
+        match family.to_uppercase().as_ref() {
+            "A" => Ok(Font::A),
+            "B" => Ok(Font::B),
+            "C" => Ok(Font::C),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid font family: {}", family),
+            )),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Font::A => "A",
+            Font::B => "B",
+            Font::C => "C",
+        }
+    }
+
+    pub(crate) fn command(self) -> &'static [u8] {
+        match self {
+            Font::A => consts::TXT_FONT_A,
+            Font::B => consts::TXT_FONT_B,
+            Font::C => consts::TXT_FONT_C,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+impl Alignment {
+    /// Parse an alignment ("lt", "ct" or "rt")
+    pub fn parse(alignment: &str) -> io::Result<Alignment> {
+        match alignment.to_uppercase().as_ref() {
+            "LT" => Ok(Alignment::Left),
+            "CT" => Ok(Alignment::Center),
+            "RT" => Ok(Alignment::Right),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid alignment: {}", alignment),
+            )),
+        }
+    }
+
+    pub(crate) fn command(self) -> &'static [u8] {
+        match self {
+            Alignment::Left => consts::TXT_ALIGN_LT,
+            Alignment::Center => consts::TXT_ALIGN_CT,
+            Alignment::Right => consts::TXT_ALIGN_RT,
+        }
+    }
+}
+
+/// Also used for the text state tracked by
+/// [Printer::state][crate::printer::Printer::state], where `None` means
+/// the value is unknown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TextStyle {
+    pub font: Option<Font>,
+    pub align: Option<Alignment>,
+    /// Character width and height magnification (1-8)
+    pub size: Option<(u8, u8)>,
+    pub bold: Option<bool>,
+    /// Underline thickness in dots, 0 turns it off
+    pub underline: Option<u8>,
+    /// White on black
+    pub reverse: Option<bool>,
+    pub upside_down: Option<bool>,
+    /// 90 degree clockwise rotation
+    pub rotate: Option<bool>,
+    pub double_strike: Option<bool>,
+    pub smoothing: Option<bool>,
+    /// Right-side character spacing in dots
+    pub spacing: Option<u8>,
+    /// Print with the second color (e.g. red) of two-color printers
+    pub second_color: Option<bool>,
+}
+
+impl TextStyle {
+    /// A style changing nothing
+    pub fn new() -> TextStyle {
+        TextStyle::default()
+    }
+
+    /// Every attribute at its power-on default
+    pub fn plain() -> TextStyle {
+        TextStyle {
+            font: Some(Font::A),
+            align: Some(Alignment::Left),
+            size: Some((1, 1)),
+            bold: Some(false),
+            underline: Some(0),
+            reverse: Some(false),
+            upside_down: Some(false),
+            rotate: Some(false),
+            double_strike: Some(false),
+            smoothing: Some(false),
+            spacing: Some(0),
+            second_color: Some(false),
+        }
+    }
+
+    /// The style going back from `changed` to `self`: known values of
+    /// `self` are restored, values unknown in `self` but changed since go
+    /// back to their defaults
+    pub fn restore(&self, changed: &TextStyle) -> TextStyle {
+        fn pick<T: Copy>(previous: Option<T>, changed: Option<T>, default: Option<T>) -> Option<T> {
+            previous.or(changed.and(default))
+        }
+        let plain = TextStyle::plain();
+        TextStyle {
+            font: pick(self.font, changed.font, plain.font),
+            align: pick(self.align, changed.align, plain.align),
+            size: pick(self.size, changed.size, plain.size),
+            bold: pick(self.bold, changed.bold, plain.bold),
+            underline: pick(self.underline, changed.underline, plain.underline),
+            reverse: pick(self.reverse, changed.reverse, plain.reverse),
+            upside_down: pick(self.upside_down, changed.upside_down, plain.upside_down),
+            rotate: pick(self.rotate, changed.rotate, plain.rotate),
+            double_strike: pick(
+                self.double_strike,
+                changed.double_strike,
+                plain.double_strike,
+            ),
+            smoothing: pick(self.smoothing, changed.smoothing, plain.smoothing),
+            spacing: pick(self.spacing, changed.spacing, plain.spacing),
+            second_color: pick(self.second_color, changed.second_color, plain.second_color),
+        }
+    }
+
+    pub fn font(mut self, font: Font) -> TextStyle {
+        self.font = Some(font);
+        self
+    }
+
+    pub fn align(mut self, align: Alignment) -> TextStyle {
+        self.align = Some(align);
+        self
+    }
+
+    pub fn size(mut self, width: u8, height: u8) -> TextStyle {
+        self.size = Some((width, height));
+        self
+    }
+
+    pub fn bold(mut self, enabled: bool) -> TextStyle {
+        self.bold = Some(enabled);
+        self
+    }
+
+    pub fn underline(mut self, n: u8) -> TextStyle {
+        self.underline = Some(n);
+        self
+    }
+
+    pub fn reverse(mut self, enabled: bool) -> TextStyle {
+        self.reverse = Some(enabled);
+        self
+    }
+
+    pub fn upside_down(mut self, enabled: bool) -> TextStyle {
+        self.upside_down = Some(enabled);
+        self
+    }
+
+    pub fn rotate(mut self, enabled: bool) -> TextStyle {
+        self.rotate = Some(enabled);
+        self
+    }
+
+    pub fn double_strike(mut self, enabled: bool) -> TextStyle {
+        self.double_strike = Some(enabled);
+        self
+    }
+
+    pub fn smoothing(mut self, enabled: bool) -> TextStyle {
+        self.smoothing = Some(enabled);
+        self
+    }
+
+    pub fn spacing(mut self, n: u8) -> TextStyle {
+        self.spacing = Some(n);
+        self
+    }
+
+    pub fn second_color(mut self, enabled: bool) -> TextStyle {
+        self.second_color = Some(enabled);
+        self
+    }
+}
@@ -0,0 +1,103 @@
+//! Transliteration of characters the printer cannot encode
+//!
+//! Applied by [Printer::print][crate::printer::Printer::print] before
+//! encoding, so "Łódź" prints as "Lodz" instead of "?od?" on code pages
+//! without Polish letters.
+
+use std::collections::HashMap;
+
+#[rustfmt::skip]
+const TABLE: &[(char, &str)] = &[
+    // Latin letters with diacritics
+    ('À', "A"), ('Á', "A"), ('Â', "A"), ('Ã', "A"), ('Ä', "A"), ('Å', "A"), ('Ą', "A"),
+    ('Ā', "A"), ('Ă', "A"), ('Æ', "AE"), ('Ç', "C"), ('Ć', "C"), ('Č', "C"), ('Ď', "D"),
+    ('Đ', "D"), ('È', "E"), ('É', "E"), ('Ê', "E"), ('Ë', "E"), ('Ę', "E"), ('Ě', "E"),
+    ('Ē', "E"), ('Ğ', "G"), ('Ì', "I"), ('Í', "I"), ('Î', "I"), ('Ï', "I"), ('İ', "I"),
+    ('Ł', "L"), ('Ľ', "L"), ('Ñ', "N"), ('Ń', "N"), ('Ň', "N"), ('Ò', "O"), ('Ó', "O"),
+    ('Ô', "O"), ('Õ', "O"), ('Ö', "O"), ('Ø', "O"), ('Ő', "O"), ('Œ', "OE"), ('Ř', "R"),
+    ('Ś', "S"), ('Š', "S"), ('Ş', "S"), ('Ť', "T"), ('Ù', "U"), ('Ú', "U"), ('Û', "U"),
+    ('Ü', "U"), ('Ů', "U"), ('Ű', "U"), ('Ý', "Y"), ('Ÿ', "Y"), ('Ź', "Z"), ('Ż', "Z"),
+    ('Ž', "Z"), ('Þ', "TH"), ('à', "a"), ('á', "a"), ('â', "a"), ('ã', "a"), ('ä', "a"),
+    ('å', "a"), ('ą', "a"), ('ā', "a"), ('ă', "a"), ('æ', "ae"), ('ç', "c"), ('ć', "c"),
+    ('č', "c"), ('ď', "d"), ('đ', "d"), ('è', "e"), ('é', "e"), ('ê', "e"), ('ë', "e"),
+    ('ę', "e"), ('ě', "e"), ('ē', "e"), ('ğ', "g"), ('ì', "i"), ('í', "i"), ('î', "i"),
+    ('ï', "i"), ('ı', "i"), ('ł', "l"), ('ľ', "l"), ('ñ', "n"), ('ń', "n"), ('ň', "n"),
+    ('ò', "o"), ('ó', "o"), ('ô', "o"), ('õ', "o"), ('ö', "o"), ('ø', "o"), ('ő', "o"),
+    ('œ', "oe"), ('ř', "r"), ('ś', "s"), ('š', "s"), ('ş', "s"), ('ß', "ss"), ('ť', "t"),
+    ('ù', "u"), ('ú', "u"), ('û', "u"), ('ü', "u"), ('ů', "u"), ('ű', "u"), ('ý', "y"),
+    ('ÿ', "y"), ('ź', "z"), ('ż', "z"), ('ž', "z"), ('þ', "th"),
+    // Currency
+    ('€', "EUR"), ('£', "GBP"), ('¥', "JPY"), ('¢', "c"), ('₽', "RUB"), ('₴', "UAH"),
+    ('₹', "INR"), ('₩', "KRW"), ('₺', "TRY"),
+    // Punctuation and symbols
+    ('‘', "'"), ('’', "'"), ('‚', "'"), ('‛', "'"), ('′', "'"), ('“', "\""), ('”', "\""),
+    ('„', "\""), ('‟', "\""), ('″', "\""), ('«', "<<"), ('»', ">>"), ('‹', "<"), ('›', ">"),
+    ('‐', "-"), ('‑', "-"), ('‒', "-"), ('–', "-"), ('—', "-"), ('―', "-"), ('−', "-"),
+    ('…', "..."), ('•', "*"), ('·', "."), ('×', "x"), ('÷', "/"), ('©', "(C)"), ('®', "(R)"),
+    ('™', "TM"), ('°', "o"), ('½', "1/2"), ('¼', "1/4"), ('¾', "3/4"), ('\u{a0}', " "),
+    ('\u{2009}', " "), ('\u{3000}', " "),
+];
+
+/// Maps characters to ASCII replacements
+///
+/// The default table covers Latin letters with diacritics, currency signs,
+/// typographic punctuation and full-width forms.
+#[derive(Clone, Debug)]
+pub struct Transliterator {
+    table: HashMap<char, String>,
+    fullwidth: bool,
+}
+
+impl Transliterator {
+    /// An empty transliterator without any replacements
+    pub fn empty() -> Transliterator {
+        Transliterator {
+            table: HashMap::new(),
+            fullwidth: false,
+        }
+    }
+
+    /// Add or override the replacement of `c`
+    pub fn insert(&mut self, c: char, replacement: &str) -> &mut Self {
+        self.table.insert(c, replacement.to_string());
+        self
+    }
+
+    /// Replacement of `c`, if any
+    pub fn get(&self, c: char) -> Option<String> {
+        if let Some(replacement) = self.table.get(&c) {
+            return Some(replacement.clone());
+        }
+        // Full-width ASCII variants (U+FF01 - U+FF5E) to half-width
+        match c as u32 {
+            code @ 0xff01..=0xff5e if self.fullwidth => {
+                char::from_u32(code - 0xfee0).map(|c| c.to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Replace the characters of `content` for which `encodable` is false
+    pub fn apply<F: Fn(char) -> bool>(&self, content: &str, encodable: F) -> String {
+        let mut rv = String::with_capacity(content.len());
+        for c in content.chars() {
+            match self.get(c) {
+                Some(replacement) if !encodable(c) => rv.push_str(&replacement),
+                _ => rv.push(c),
+            }
+        }
+        rv
+    }
+}
+
+impl Default for Transliterator {
+    fn default() -> Transliterator {
+        Transliterator {
+            table: TABLE
+                .iter()
+                .map(|&(c, replacement)| (c, replacement.to_string()))
+                .collect(),
+            fullwidth: true,
+        }
+    }
+}
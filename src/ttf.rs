@@ -0,0 +1,366 @@
+//! Render text with a TrueType / OpenType font into an [Image]
+//!
+//! For scripts no code page can print (Arabic, Hebrew, Thai, Devanagari,
+//! emoji...). Text is shaped with `rustybuzz`, reordered for right-to-left
+//! scripts with `unicode-bidi` and word wrapped at the paper width. The
+//! resulting image can be printed with [Printer::raster][crate::printer::Printer::raster].
+//!
+//! Color bitmap glyphs (e.g. color emoji) are not rendered, use a font with
+//! outlines for them.
+
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path;
+
+use ab_glyph::{point, Font, FontRef, Glyph, GlyphId, PxScale, ScaleFont};
+use image::{DynamicImage, ImageBuffer, Rgb};
+use rustybuzz::{Direction, Face, UnicodeBuffer};
+use unicode_bidi::BidiInfo;
+
+use crate::img::Image;
+use crate::style::Alignment;
+
+/// A positioned glyph of a laid out line, in pixels
+struct PlacedGlyph {
+    id: u16,
+    x: f32,
+    y: f32,
+}
+
+/// Renders text with a user supplied font at a fixed paper width
+///
+/// # Example
+/// ```no_run
+/// use escposify::ttf::TextRenderer;
+///
+/// let renderer = TextRenderer::from_path("NotoSansArabic-Regular.ttf", 32.0, 576).unwrap();
+/// let image = renderer.render("مرحبا بالعالم");
+/// ```
+pub struct TextRenderer {
+    data: Vec<u8>,
+    size: f32,
+    width: u32,
+    align: Option<Alignment>,
+}
+
+impl TextRenderer {
+    /// `size` is the line height in pixels (dots), `width` the paper width
+    /// in dots
+    pub fn new(data: Vec<u8>, size: f32, width: u32) -> io::Result<TextRenderer> {
+        if FontRef::try_from_slice(&data).is_err() || Face::from_slice(&data, 0).is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Invalid TrueType / OpenType font",
+            ));
+        }
+        Ok(TextRenderer {
+            data,
+            size,
+            width,
+            align: None,
+        })
+    }
+
+    pub fn from_path<P: AsRef<path::Path>>(
+        path: P,
+        size: f32,
+        width: u32,
+    ) -> io::Result<TextRenderer> {
+        TextRenderer::new(fs::read(path)?, size, width)
+    }
+
+    /// Align lines left ("lt"), centered ("ct") or right ("rt"). By default
+    /// lines follow the direction of their paragraph.
+    pub fn set_align(&mut self, alignment: &str) -> io::Result<()> {
+        self.align = Some(Alignment::parse(alignment)?);
+        Ok(())
+    }
+
+    /// Lay out and render `text`, one or more lines per paragraph (`\n`)
+    pub fn render(&self, text: &str) -> Image {
+        let font = FontRef::try_from_slice(&self.data).expect("font checked in new");
+        let face = Face::from_slice(&self.data, 0).expect("font checked in new");
+        let scaled = font.as_scaled(PxScale::from(self.size));
+        let line_height = (scaled.ascent() - scaled.descent() + scaled.line_gap()).ceil();
+        let units = scaled.h_scale_factor();
+
+        let mut lines: Vec<(Alignment, Vec<PlacedGlyph>, f32)> = vec![];
+        for paragraph in text.split('\n') {
+            let bidi = BidiInfo::new(paragraph, None);
+            let rtl = bidi
+                .paragraphs
+                .first()
+                .map_or(false, |para| para.level.is_rtl());
+            let align = self.align.unwrap_or(if rtl {
+                Alignment::Right
+            } else {
+                Alignment::Left
+            });
+            if paragraph.is_empty() {
+                lines.push((align, vec![], 0.0));
+                continue;
+            }
+            let ranges = wrap(paragraph, self.width as f32, |word| {
+                char_advances(&face, units, word)
+            });
+            for range in ranges {
+                let mut glyphs = vec![];
+                let mut x = 0.0;
+                for (run, rtl) in visual_runs(&bidi, range) {
+                    let shaped = shape(&face, &paragraph[run], rtl);
+                    for (info, pos) in shaped.glyph_infos().iter().zip(shaped.glyph_positions()) {
+                        glyphs.push(PlacedGlyph {
+                            id: info.glyph_id as u16,
+                            x: x + pos.x_offset as f32 * units,
+                            y: -pos.y_offset as f32 * units,
+                        });
+                        x += pos.x_advance as f32 * units;
+                    }
+                }
+                lines.push((align, glyphs, x));
+            }
+        }
+
+        let height = ((lines.len() as f32 * line_height) as u32).max(1);
+        let mut buf = ImageBuffer::from_pixel(self.width, height, Rgb([0xFF, 0xFF, 0xFF]));
+        for (i, (align, glyphs, line_width)) in lines.iter().enumerate() {
+            let left = match align {
+                Alignment::Left => 0.0,
+                Alignment::Center => ((self.width as f32 - line_width) / 2.0).max(0.0),
+                Alignment::Right => (self.width as f32 - line_width).max(0.0),
+            };
+            let baseline = i as f32 * line_height + scaled.ascent();
+            for glyph in glyphs {
+                let glyph = Glyph {
+                    id: GlyphId(glyph.id),
+                    scale: scaled.scale(),
+                    position: point(left + glyph.x, baseline + glyph.y),
+                };
+                if let Some(outlined) = font.outline_glyph(glyph) {
+                    let bounds = outlined.px_bounds();
+                    outlined.draw(|x, y, coverage| {
+                        let x = bounds.min.x as i32 + x as i32;
+                        let y = bounds.min.y as i32 + y as i32;
+                        if coverage >= 0.5
+                            && x >= 0
+                            && y >= 0
+                            && (x as u32) < self.width
+                            && (y as u32) < height
+                        {
+                            buf.put_pixel(x as u32, y as u32, Rgb([0, 0, 0]));
+                        }
+                    });
+                }
+            }
+        }
+        Image::from(DynamicImage::ImageRgb8(buf))
+    }
+}
+
+fn shape(face: &Face, text: &str, rtl: bool) -> rustybuzz::GlyphBuffer {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.guess_segment_properties();
+    buffer.set_direction(if rtl {
+        Direction::RightToLeft
+    } else {
+        Direction::LeftToRight
+    });
+    rustybuzz::shape(face, &[], buffer)
+}
+
+/// Advance of every character of `text` in pixels, glyphs of a cluster
+/// count for its first character
+fn char_advances(face: &Face, units: f32, text: &str) -> Vec<f32> {
+    let offsets: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+    let mut advances = vec![0.0; offsets.len()];
+    let shaped = shape(face, text, false);
+    for (info, pos) in shaped.glyph_infos().iter().zip(shaped.glyph_positions()) {
+        let i = match offsets.binary_search(&(info.cluster as usize)) {
+            Ok(i) => i,
+            Err(i) => i.saturating_sub(1),
+        };
+        if let Some(advance) = advances.get_mut(i) {
+            *advance += pos.x_advance as f32 * units;
+        }
+    }
+    advances
+}
+
+/// Split a paragraph into byte ranges of lines fitting `max_width`,
+/// breaking at whitespace and inside words only when they don't fit.
+/// `advances` gives the width of every character of a word.
+fn wrap<F>(paragraph: &str, max_width: f32, advances: F) -> Vec<Range<usize>>
+where
+    F: Fn(&str) -> Vec<f32>,
+{
+    let trimmed =
+        |range: Range<usize>| range.start..range.start + paragraph[range].trim_end().len();
+
+    let mut lines = vec![];
+    let mut start = 0;
+    let mut end = 0;
+    let mut line_width = 0.0;
+    for (i, word) in split_words(paragraph) {
+        let word_end = i + word.len();
+        let word_advances = advances(word);
+        let word_width: f32 = word_advances.iter().sum();
+        if line_width + word_width <= max_width {
+            end = word_end;
+            line_width += word_width;
+            continue;
+        }
+        if end > start {
+            lines.push(trimmed(start..end));
+        }
+        end = word_end;
+        // Skip the whitespace the line was broken at
+        if word.trim().is_empty() {
+            start = word_end;
+            line_width = 0.0;
+            continue;
+        }
+        start = i;
+        line_width = word_width;
+        // Break words wider than the paper at character boundaries
+        let mut chars = word.char_indices().zip(word_advances).peekable();
+        while line_width > max_width {
+            let mut cut_width = 0.0;
+            while let Some(&(_, advance)) = chars.peek() {
+                if cut_width > 0.0 && cut_width + advance > max_width {
+                    break;
+                }
+                cut_width += advance;
+                chars.next();
+            }
+            let cut = chars.peek().map_or(word_end, |&((j, _), _)| i + j);
+            lines.push(start..cut);
+            start = cut;
+            line_width -= cut_width;
+        }
+    }
+    if end > start || lines.is_empty() {
+        lines.push(start..end);
+    }
+    lines
+}
+
+/// Byte ranges of the bidi runs of a line in visual order, with whether
+/// they are right-to-left. Paragraphs within the line (e.g. after U+2029)
+/// follow each other from left to right.
+fn visual_runs(bidi: &BidiInfo, line: Range<usize>) -> Vec<(Range<usize>, bool)> {
+    let mut visual = vec![];
+    for para in &bidi.paragraphs {
+        let range = line.start.max(para.range.start)..line.end.min(para.range.end);
+        if range.is_empty() {
+            continue;
+        }
+        let (levels, runs) = bidi.visual_runs(para, range);
+        visual.extend(runs.into_iter().map(|run| {
+            let rtl = levels[run.start].is_rtl();
+            (run, rtl)
+        }));
+    }
+    visual
+}
+
+/// Words and the whitespace between them, with their byte offsets
+fn split_words(text: &str) -> Vec<(usize, &str)> {
+    let mut words = vec![];
+    let mut start = 0;
+    let mut space = None;
+    for (i, c) in text.char_indices() {
+        let is_space = c.is_whitespace();
+        if space.map_or(false, |space| space != is_space) {
+            words.push((start, &text[start..i]));
+            start = i;
+        }
+        space = Some(is_space);
+    }
+    if start < text.len() {
+        words.push((start, &text[start..]));
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(paragraph: &str, max_width: f32) -> Vec<&str> {
+        wrap(paragraph, max_width, |word| {
+            vec![10.0; word.chars().count()]
+        })
+        .into_iter()
+        .map(|range| &paragraph[range])
+        .collect()
+    }
+
+    #[test]
+    fn words() {
+        assert_eq!(
+            split_words("ab  c\td"),
+            vec![(0, "ab"), (2, "  "), (4, "c"), (5, "\t"), (6, "d")]
+        );
+        assert_eq!(split_words(" é "), vec![(0, " "), (1, "é"), (3, " ")]);
+        assert!(split_words("").is_empty());
+    }
+
+    #[test]
+    fn wrap_at_whitespace() {
+        assert_eq!(
+            lines("hello world foo", 50.0),
+            vec!["hello", "world", "foo"]
+        );
+        assert_eq!(lines("ab cd ef", 50.0), vec!["ab cd", "ef"]);
+    }
+
+    #[test]
+    fn wrap_long_words() {
+        assert_eq!(
+            lines("abcdefghijkl xy", 50.0),
+            vec!["abcde", "fghij", "kl xy"]
+        );
+        assert_eq!(lines("ééééééé", 50.0), vec!["ééééé", "éé"]);
+        // At least one character per line
+        assert_eq!(lines("abc", 5.0), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn wrap_empty() {
+        assert_eq!(wrap("", 50.0, |_| vec![]), vec![0..0]);
+    }
+
+    #[test]
+    fn rtl_run_order() {
+        let runs = |text: &str| {
+            let bidi = BidiInfo::new(text, None);
+            visual_runs(&bidi, 0..text.len())
+                .into_iter()
+                .map(|(run, rtl)| (text[run].to_string(), rtl))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            runs("abc שלום def"),
+            vec![
+                ("abc ".to_string(), false),
+                ("שלום".to_string(), true),
+                (" def".to_string(), false)
+            ]
+        );
+        assert_eq!(
+            runs("שלום abc"),
+            vec![("abc".to_string(), false), ("שלום ".to_string(), true)]
+        );
+        assert_eq!(
+            runs("abc\u{2029}שלום def"),
+            vec![
+                ("abc\u{2029}".to_string(), false),
+                ("def".to_string(), false),
+                ("שלום ".to_string(), true)
+            ]
+        );
+        assert!(runs("").is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+extern crate escposify;
+
+use escposify::codepage::CodePages;
+use escposify::printer::Printer;
+use escposify::profile::Profile;
+use escposify::translit::Transliterator;
+
+use encoding::all::{UTF_8, WINDOWS_1252};
+use encoding::types::EncoderTrap;
+
+#[test]
+fn code_page_runs() {
+    let pages = CodePages::new(vec![(0, "cp437"), (17, "ibm866"), (47, "windows-1253")]);
+    let runs = pages
+        .encode("Grüße Ελλάδα Привет", None, EncoderTrap::Replace)
+        .unwrap();
+    let switches: Vec<Option<u8>> = runs.iter().map(|(page, _)| *page).collect();
+    assert_eq!(switches, vec![None, Some(0), Some(47), Some(17)]);
+    assert_eq!(runs[1].1, b"\x81\xe1e ");
+
+    // Nothing to switch when the current page already has every character
+    let runs = pages
+        .encode("Grüße", Some(0), EncoderTrap::Replace)
+        .unwrap();
+    assert_eq!(runs, vec![(None, b"Gr\x81\xe1e".to_vec())]);
+
+    let runs = pages.encode("a✓b", None, EncoderTrap::Replace).unwrap();
+    assert_eq!(runs, vec![(None, b"a?b".to_vec())]);
+    assert!(pages.encode("a✓b", None, EncoderTrap::Strict).is_err());
+}
+
+#[test]
+fn auto_code_page() {
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        printer.set_profile(Profile::get("TM-T88V").unwrap());
+        printer.set_auto_code_page(true);
+        printer.print("Straße Привет Пока").unwrap();
+        printer.flush().unwrap();
+    }
+    assert_eq!(
+        buf,
+        b"Stra\x1b\x74\x00\xe1e \x1b\x74\x11\x8f\xe0\xa8\xa2\xa5\xe2 \x8f\xae\xaa\xa0".to_vec()
+    );
+}
+
+#[test]
+fn scoped_codec() {
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        printer
+            .with_codec(WINDOWS_1252, Some("germany"), |p| p.print("ä"))
+            .unwrap();
+        printer.print("ä").unwrap();
+        printer.flush().unwrap();
+    }
+    assert_eq!(
+        buf,
+        b"\x1b\x74\x10\x1b\x52\x02\xe4\x1b\x74\x00\x1b\x52\x00\xc3\xa4".to_vec()
+    );
+}
+
+#[test]
+fn codec_without_code_page() {
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        printer.set_codec(WINDOWS_1252).unwrap();
+        printer.set_codec(UTF_8).unwrap();
+        printer.print("ä").unwrap();
+        printer.flush().unwrap();
+    }
+    assert_eq!(buf, b"\x1b\x74\x10\x1b\x74\x00\xc3\xa4".to_vec());
+}
+
+#[test]
+fn cjk_text() {
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        printer.print_cjk("A敏捷B").unwrap();
+        printer.kanji_encoding("jis").unwrap();
+        printer.print_cjk("テスト").unwrap();
+        printer.flush().unwrap();
+    }
+    assert_eq!(
+        buf,
+        b"A\x1c\x26\xc3\xf4\xbd\xdd\x1c\x2eB\x1c\x43\x00\x1c\x26\x25\x46\x25\x39\x25\x48\x1c\x2e"
+            .to_vec()
+    );
+}
+
+#[test]
+fn cjk_mixed_text() {
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        printer.set_codec(WINDOWS_1252).unwrap();
+        printer.print_cjk("Café 咖啡 €").unwrap();
+        printer.flush().unwrap();
+    }
+    assert_eq!(
+        buf,
+        b"\x1b\x74\x10Caf\xe9 \x1c\x26\xbf\xa7\xb7\xc8\x1c\x2e \x80".to_vec()
+    );
+}
+
+#[test]
+fn transliteration() {
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, Some(WINDOWS_1252), None);
+        printer.print("Łódź ✓").unwrap();
+        printer.set_transliterator(Some(Transliterator::default()));
+        printer.print("Łódź ＡＢ€").unwrap();
+        printer.flush().unwrap();
+    }
+    assert_eq!(buf, b"?\xf3d? ?L\xf3dz AB\x80".to_vec());
+}
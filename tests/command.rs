@@ -0,0 +1,83 @@
+extern crate escposify;
+
+use escposify::command::{BarcodeKind, BarcodeText, Command, Document};
+use escposify::encoder::Encoder;
+use escposify::printer::{CutMode, Printer};
+use escposify::style::{Alignment, Font};
+
+fn receipt() -> Document {
+    vec![
+        Command::Init,
+        Command::Align(Alignment::Center),
+        Command::Bold(true),
+        Command::Text("RECEIPT\n".to_string()),
+        Command::Bold(true),
+        Command::Barcode {
+            code: "12345670".to_string(),
+            kind: BarcodeKind::Ean8,
+            text: BarcodeText::Below,
+            font: Font::A,
+            width: 2,
+            height: 50,
+        },
+        Command::Feed(1),
+        Command::Cut {
+            mode: CutMode::Partial,
+            feed: Some(3),
+        },
+    ]
+    .into()
+}
+
+#[test]
+fn encode_document() {
+    let bytes = receipt().encode().unwrap();
+    assert_eq!(
+        bytes,
+        b"\x1b\x40\x1b\x61\x01\x1b\x45\x01RECEIPT\n\
+          \x1d\x77\x02\x1d\x68\x32\x1d\x66\x00\x1d\x48\x02\x1d\x6b\x0312345670\x00\
+          \n\x1d\x56\x42\x03"
+            .to_vec()
+    );
+
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        printer
+            .chain_hwinit()
+            .unwrap()
+            .chain_align("ct")
+            .unwrap()
+            .chain_bold(true)
+            .unwrap()
+            .chain_println("RECEIPT")
+            .unwrap()
+            .chain_barcode("12345670", "EAN8", "BLW", "A", 2, 50)
+            .unwrap()
+            .chain_feed(1)
+            .unwrap()
+            .chain_cut(CutMode::Partial, Some(3))
+            .unwrap()
+            .flush()
+            .unwrap();
+    }
+    assert_eq!(buf, bytes);
+}
+
+#[test]
+fn print_document() {
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        printer.print_document(&receipt()).unwrap();
+        // The printer tracks the state set by the document
+        assert_eq!(printer.bold(true).unwrap(), 0);
+        assert!(printer.command(Command::Underline(3)).is_err());
+        printer.flush().unwrap();
+    }
+    assert_eq!(buf, receipt().encode().unwrap());
+
+    let mut encoder = Encoder::default();
+    let nested = Command::DefineMacro(vec![Command::DefineMacro(vec![])]);
+    assert!(encoder.encode(&nested).is_err());
+}
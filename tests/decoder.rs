@@ -0,0 +1,129 @@
+extern crate escposify;
+extern crate image;
+
+use escposify::command::{
+    BarcodeKind, BarcodeText, Command, Document, DrawerPin, QrLevel, RasterMode,
+};
+use escposify::decoder::{self, Item};
+use escposify::img::Image;
+use escposify::printer::{CutMode, Printer};
+use escposify::style::{Alignment, Font};
+
+fn checkerboard() -> Image {
+    let img_buf = image::RgbImage::from_fn(16, 4, |x, y| {
+        if (x + y) % 2 == 0 {
+            image::Rgb([0, 0, 0])
+        } else {
+            image::Rgb([255, 255, 255])
+        }
+    });
+    Image::from(image::DynamicImage::ImageRgb8(img_buf))
+}
+
+#[test]
+fn roundtrip() {
+    let document: Document = vec![
+        Command::Init,
+        Command::Align(Alignment::Center),
+        Command::Bold(true),
+        Command::Text("RECEIPT\n".to_string()),
+        Command::Size {
+            width: 2,
+            height: 1,
+        },
+        Command::Barcode {
+            code: "12345670".to_string(),
+            kind: BarcodeKind::Ean8,
+            text: BarcodeText::Below,
+            font: Font::A,
+            width: 2,
+            height: 50,
+        },
+        Command::Raster {
+            image: checkerboard(),
+            mode: RasterMode::Normal,
+        },
+        Command::Cut {
+            mode: CutMode::Partial,
+            feed: Some(3),
+        },
+    ]
+    .into();
+    let entries = decoder::decode(&document.encode().unwrap());
+    let commands: Vec<Command> = entries
+        .into_iter()
+        .filter_map(|entry| match entry.item {
+            Item::Command(command) => Some(command),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(commands, document.commands);
+}
+
+#[test]
+fn cash_drawer() {
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        printer.chain_cashdraw(2).unwrap().chain_print("A").unwrap();
+        printer.flush().unwrap();
+    }
+    assert_eq!(buf, b"\x1b\x70\x00\x19\xfaA".to_vec());
+    let commands: Vec<Item> = decoder::decode(&buf)
+        .into_iter()
+        .map(|entry| entry.item)
+        .collect();
+    assert_eq!(
+        commands,
+        vec![
+            Item::Command(Command::CashDraw(DrawerPin::Pin2)),
+            Item::Command(Command::Text("A".to_string())),
+        ]
+    );
+}
+
+#[test]
+fn qr_code_functions() {
+    let data = b"\x1d\x28\x6b\x03\x00\x31\x43\x06\
+                 \x1d\x28\x6b\x03\x00\x31\x45\x31\
+                 \x1d\x28\x6b\x08\x00\x31\x50\x30HELLO\
+                 \x1d\x28\x6b\x03\x00\x31\x51\x30";
+    let document = decoder::document(data);
+    assert_eq!(
+        document.commands,
+        vec![Command::QrCode {
+            code: "HELLO".to_string(),
+            version: 0,
+            level: QrLevel::M,
+            size: 6,
+        }]
+    );
+}
+
+#[test]
+fn unknown_commands() {
+    let entries = decoder::decode(b"\x1b\x01A\x00\x1d\x56");
+    let items: Vec<Item> = entries.iter().map(|entry| entry.item.clone()).collect();
+    assert_eq!(
+        items,
+        vec![
+            Item::Unknown(b"\x1b\x01".to_vec()),
+            Item::Command(Command::Text("A".to_string())),
+            Item::Unknown(b"\x00".to_vec()),
+            Item::Unknown(b"\x1d\x56".to_vec()),
+        ]
+    );
+    assert_eq!(entries[3].offset, 4);
+}
+
+#[test]
+fn listing() {
+    let entries = decoder::decode(b"\x1b\x45\x01\x1d\x28\x6b\x03\x00\x31\x43\x06");
+    let listing = decoder::listing(&entries);
+    let lines: Vec<&str> = listing.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("000000  ESC E"));
+    assert!(lines[0].ends_with("Bold(true)"));
+    assert!(lines[1].starts_with("000003  GS ("));
+    assert!(lines[1].ends_with("QR code: set module size [49, 67, 6]"));
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "emulator")]
+
+extern crate escposify;
+extern crate image;
+
+use escposify::command::{BarcodeKind, BarcodeText, Command, Document, RasterMode};
+use escposify::emulator::Emulator;
+use escposify::img::Image;
+use escposify::printer::{CutMode, Printer};
+use escposify::style::Font;
+
+fn is_black(receipt: &image::RgbImage, x: u32, y: u32) -> bool {
+    receipt.get_pixel(x, y).0 == [0, 0, 0]
+}
+
+#[test]
+fn printer_and_document() {
+    let mut emulator = Emulator::new(384);
+    {
+        let mut printer = Printer::new(&mut emulator, None, None);
+        printer
+            .chain_align("ct")
+            .unwrap()
+            .chain_bold(true)
+            .unwrap()
+            .chain_println("RECEIPT")
+            .unwrap()
+            .chain_cut(CutMode::Full, None)
+            .unwrap()
+            .flush()
+            .unwrap();
+    }
+    let receipt = emulator.image();
+    assert_eq!(receipt.width(), 384);
+    // A line of text and the cut
+    assert_eq!(receipt.height(), 30 + 16);
+    assert!((0..receipt.width()).any(|x| is_black(&receipt, x, 12)));
+    assert!(!is_black(&receipt, 0, 12));
+    assert!(is_black(&receipt, 0, 38) && !is_black(&receipt, 4, 38));
+
+    let document: Document = vec![
+        Command::line("RECEIPT"),
+        Command::Cut {
+            mode: CutMode::Full,
+            feed: None,
+        },
+    ]
+    .into();
+    let mut other = Emulator::new(384);
+    other.print(b"\x1b\x61\x01\x1b\x45\x01");
+    other.print_document(&document).unwrap();
+    assert_eq!(other.image(), receipt);
+}
+
+#[test]
+fn barcode() {
+    let mut emulator = Emulator::new(384);
+    let document: Document = vec![Command::Barcode {
+        code: "1234567".to_string(),
+        kind: BarcodeKind::Ean8,
+        text: BarcodeText::Off,
+        font: Font::A,
+        width: 2,
+        height: 50,
+    }]
+    .into();
+    emulator.print_document(&document).unwrap();
+    let receipt = emulator.image();
+    assert_eq!(receipt.height(), 50);
+    // 67 modules of 2 dots, starting and ending with a guard bar
+    let black: Vec<u32> = (0..384).filter(|&x| is_black(&receipt, x, 25)).collect();
+    assert_eq!(black.first(), Some(&0));
+    assert_eq!(black.last(), Some(&133));
+}
+
+#[test]
+fn raster() {
+    let img_buf = image::RgbImage::from_fn(16, 2, |x, y| {
+        if (x + y) % 2 == 0 {
+            image::Rgb([0, 0, 0])
+        } else {
+            image::Rgb([255, 255, 255])
+        }
+    });
+    let image = Image::from(image::DynamicImage::ImageRgb8(img_buf));
+    let mut emulator = Emulator::new(384);
+    let document: Document = vec![Command::Raster {
+        image,
+        mode: RasterMode::DoubleWidth,
+    }]
+    .into();
+    emulator.print_document(&document).unwrap();
+    let receipt = emulator.image();
+    assert_eq!(receipt.height(), 2);
+    assert!(is_black(&receipt, 0, 0) && is_black(&receipt, 1, 0));
+    assert!(!is_black(&receipt, 2, 0) && is_black(&receipt, 2, 1));
+    assert!(!is_black(&receipt, 32, 0));
+}
+
+#[test]
+fn qr_module_size() {
+    let mut emulator = Emulator::new(384);
+    // Module size 255 is printed with the largest size, 16
+    emulator.print(b"\x1d\x28\x6b\x03\x00\x31\x43\xff");
+    emulator.print(b"\x1d\x28\x6b\x08\x00\x31\x50\x30HELLO");
+    emulator.print(b"\x1d\x28\x6b\x03\x00\x31\x51\x30");
+    // Version 1 has 21 modules
+    assert_eq!(emulator.image().height(), 21 * 16);
+}
+
+#[test]
+fn upside_down_position() {
+    let mut emulator = Emulator::new(384);
+    // Upside down "ABC", then back to the start of the line for "D"
+    emulator.print(b"\x1b\x7b\x01ABC\x1b\x24\x00\x00D\n");
+    let receipt = emulator.image();
+    assert_eq!(receipt.height(), 30);
+    // Rotated as a whole: "D" over "A" at the right end of the 3 characters
+    assert!((24..36).any(|x| (0..30).any(|y| is_black(&receipt, x, y))));
+    assert!(!(36..384).any(|x| (0..30).any(|y| is_black(&receipt, x, y))));
+}
@@ -0,0 +1,103 @@
+extern crate escposify;
+
+use std::io::{self, Read, Write};
+
+use escposify::info::{parse_custom_value, parse_info_block, PrinterInfo};
+use escposify::printer::Printer;
+
+/// Fake device answering queries from a canned response buffer
+struct Device {
+    response: io::Cursor<Vec<u8>>,
+}
+
+impl Read for Device {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.response.read(buf)? {
+            0 => Err(io::Error::new(io::ErrorKind::TimedOut, "no answer")),
+            n => Ok(n),
+        }
+    }
+}
+
+impl Write for Device {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn info_block() {
+    assert_eq!(
+        parse_info_block(b"_TM-T88V\x00"),
+        Some("TM-T88V".to_string())
+    );
+    assert_eq!(parse_info_block(b"_EPSON"), Some("EPSON".to_string()));
+    assert_eq!(parse_info_block(b"TM-T88V\x00"), None);
+}
+
+#[test]
+fn custom_value() {
+    assert_eq!(
+        parse_custom_value(b"\x37\x21\x33\x1f\x35\x37\x36\x00"),
+        Some((3, 576))
+    );
+    // Empty or short replies
+    assert_eq!(parse_custom_value(b"\x37\x00"), None);
+    assert_eq!(parse_custom_value(b"\x37\x21\x00"), None);
+}
+
+#[test]
+fn printer_info() {
+    let mut response = vec![0x20, 0x02, 0x0f];
+    response.extend_from_slice(b"_30.01 ESC/POS\x00");
+    response.extend_from_slice(b"\x14\x00_EPSON\x00"); // leading status bytes are skipped
+    response.extend_from_slice(b"_TM-T88V\x00");
+    let device = Device {
+        response: io::Cursor::new(response),
+    };
+    let mut printer = Printer::new(device, None, None);
+
+    let info = printer.printer_info().unwrap();
+    assert_eq!(
+        info,
+        PrinterInfo {
+            model_id: Some(0x20),
+            type_id: Some(0x02),
+            rom_version: Some(0x0f),
+            firmware: Some("30.01 ESC/POS".to_string()),
+            manufacturer: Some("EPSON".to_string()),
+            model_name: Some("TM-T88V".to_string()),
+            serial_number: None,
+            font_of_language: None,
+        }
+    );
+    assert!(info.has_autocutter());
+    assert!(!info.supports_multibyte());
+}
+
+#[test]
+fn customized_value() {
+    let device = Device {
+        response: io::Cursor::new(b"\x37\x27\x33\x1f\x38\x30\x00".to_vec()),
+    };
+    let mut printer = Printer::new(device, None, None);
+    assert_eq!(printer.customized_value(3).unwrap(), 80);
+}
+
+#[test]
+fn drawer_status() {
+    let device = Device {
+        response: io::Cursor::new(vec![0x16, 0x12, 0x40]),
+    };
+    let mut printer = Printer::new(device, None, None);
+    assert!(printer.drawer_open().unwrap());
+    assert!(!printer.drawer_open().unwrap());
+    assert_eq!(
+        printer.drawer_open().unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+}
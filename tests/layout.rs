@@ -0,0 +1,48 @@
+extern crate escposify;
+
+use escposify::layout::{justify, pad, row, separator, wrap, Column, Table};
+use escposify::style::Alignment;
+
+#[test]
+fn word_wrap() {
+    assert_eq!(
+        wrap("The quick brown fox jumps over the lazy dog", 10),
+        vec!["The quick", "brown fox", "jumps over", "the lazy", "dog"]
+    );
+    assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+    assert_eq!(wrap("敏捷的棕色狐狸", 6), vec!["敏捷的", "棕色狐", "狸"]);
+    assert_eq!(wrap("a\n\nb", 4), vec!["a", "", "b"]);
+}
+
+#[test]
+fn cells() {
+    assert_eq!(pad("ab", 5, Alignment::Right), "   ab");
+    assert_eq!(pad("狐狸", 6, Alignment::Center), " 狐狸 ");
+    assert_eq!(justify("Total", "12.50", '.', 20), "Total ........ 12.50");
+    assert_eq!(separator('=', 5), "=====");
+    // Emoji with variation selector 16
+    assert_eq!(pad("❤️", 1, Alignment::Left), "❤️");
+    assert_eq!(pad("a❤️", 2, Alignment::Right), "a❤️");
+    assert_eq!(pad("❤️", 3, Alignment::Right), "  ❤️");
+
+    let columns = [
+        Column::new(0),
+        Column::new(3).align(Alignment::Right),
+        Column::new(6).align(Alignment::Right).truncate(),
+    ];
+    assert_eq!(
+        row(&columns, &["Blueberry muffin", "1", "123.500"], 1, 20),
+        vec!["Blueberry   1 123.50", "muffin"]
+    );
+}
+
+#[test]
+fn table() {
+    let table = Table::new(vec![Column::new(0), Column::new(5).align(Alignment::Right)])
+        .header(&["Item", "Price"])
+        .row(&["Tea", "2.00"]);
+    assert_eq!(
+        table.lines(12),
+        vec!["Item   Price", "------------", "Tea     2.00"]
+    );
+}
@@ -0,0 +1,80 @@
+extern crate escposify;
+
+use std::io;
+
+use escposify::command::Command;
+use escposify::encoder::Encoder;
+use escposify::page::{Area, Direction};
+use escposify::printer::Printer;
+
+#[test]
+fn page_mode() {
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        let area = Area::new(0, 0, 200, 100);
+        printer
+            .page_mode(area, Direction::BottomToTop, |page| {
+                assert!(page.move_to(150, 10).is_err());
+                page.chain_move_to(10, 150)?.print("A")
+            })
+            .unwrap();
+        printer.flush().unwrap();
+    }
+    assert_eq!(
+        buf,
+        b"\x1b\x4c\x1b\x57\x00\x00\x00\x00\xc8\x00\x64\x00\x1b\x54\x01\
+          \x1b\x24\x0a\x00\x1d\x24\x96\x00A\x0c"
+            .to_vec()
+    );
+}
+
+#[test]
+fn page_mode_cancel() {
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        let err = printer
+            .page_mode(Area::new(0, 0, 600, 100), Direction::LeftToRight, |page| {
+                page.print("never")
+            })
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        printer.flush().unwrap();
+    }
+    assert_eq!(buf, b"\x1b\x4c\x18\x1b\x53".to_vec());
+}
+
+#[test]
+fn page_area_overflow() {
+    let mut printer = Printer::new(io::sink(), None, None);
+    let err = printer
+        .page_mode(
+            Area::new(u16::MAX - 10, 0, 100, 100),
+            Direction::LeftToRight,
+            |page| page.print("never"),
+        )
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn page_commands() {
+    let mut encoder = Encoder::default();
+    assert!(encoder.encode(&Command::PageMode).is_ok());
+    // Taller than the page height of the profile
+    let area = Area::new(0, 900, 576, 100);
+    assert!(encoder.encode(&Command::PageArea(area)).is_err());
+    let area = Area::new(0, 0, 200, 100);
+    assert!(encoder.encode(&Command::PageArea(area)).is_ok());
+    assert!(encoder
+        .encode(&Command::PagePosition { x: 150, y: 10 })
+        .is_ok());
+    assert!(encoder
+        .encode(&Command::PageDirection(Direction::BottomToTop))
+        .is_ok());
+    let err = encoder
+        .encode(&Command::PagePosition { x: 150, y: 10 })
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,82 @@
+extern crate escposify;
+
+use std::io;
+
+use escposify::info::PrinterInfo;
+use escposify::printer::{CutMode, Printer};
+use escposify::profile::{Graphics, Profile};
+
+#[test]
+fn bundled_profiles() {
+    let profiles = Profile::all();
+    assert!(profiles.iter().any(|p| p.name == "default"));
+    for profile in profiles {
+        assert!(!profile.fonts.is_empty(), "{} has no fonts", profile.name);
+        assert!(profile.dot_width > 0);
+    }
+
+    let profile = Profile::get("xp-58").unwrap();
+    assert_eq!(profile.font("a").unwrap().columns, 32);
+    assert_eq!(profile.graphics, Graphics::BitImage);
+    assert!(!profile.cutter);
+}
+
+#[test]
+fn profile_from_info() {
+    let info = PrinterInfo {
+        model_name: Some("TM-T88V".to_string()),
+        ..PrinterInfo::default()
+    };
+    assert_eq!(Profile::from_info(&info).name, "TM-T88V");
+    assert_eq!(Profile::from_info(&PrinterInfo::default()).name, "default");
+}
+
+#[test]
+fn unsupported_commands() {
+    let mut printer = Printer::new(io::sink(), None, None);
+    printer.set_profile(Profile::get("TM-T88V").unwrap());
+
+    assert!(printer.font("B").is_ok());
+    let err = printer.font("C").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+
+    assert!(printer.reverse_feed_lines(1).is_ok());
+    printer.set_profile(Profile::get("XP-58").unwrap());
+    let err = printer.reverse_feed_dots(10).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    let err = printer.cut(CutMode::Full, None).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+}
+
+#[test]
+fn buzzer() {
+    use std::time::Duration;
+
+    use escposify::printer::BuzzerPattern;
+
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        assert!(printer.beep(10, Duration::from_millis(100)).is_err());
+        assert!(printer.beep(1, Duration::from_millis(20)).is_err());
+        printer
+            .chain_beep(3, Duration::from_millis(100))
+            .unwrap()
+            .chain_buzzer(BuzzerPattern::B, 2, Duration::from_millis(500))
+            .unwrap();
+        // No buzzer: ignored with a warning
+        printer.set_profile(Profile::get("TM-T88V").unwrap());
+        assert_eq!(printer.beep(1, Duration::from_millis(50)).unwrap(), 0);
+        printer
+            .chain_external_buzzer(BuzzerPattern::A, 1, Duration::from_secs(1), Duration::ZERO)
+            .unwrap()
+            .flush()
+            .unwrap();
+    }
+    assert_eq!(
+        buf,
+        b"\x1b\x42\x03\x02\x1b\x28\x41\x04\x00\x30\x02\x02\x05\
+          \x1b\x28\x41\x05\x00\x61\x01\x01\x0a\x00"
+            .to_vec()
+    );
+}
@@ -0,0 +1,85 @@
+extern crate escposify;
+
+use escposify::command::{Command, Document};
+use escposify::decoder;
+use escposify::printer::{CutMode, Printer};
+use escposify::profile::Profile;
+use escposify::render;
+use escposify::style::{Alignment, Font};
+
+fn receipt() -> Document {
+    vec![
+        Command::Align(Alignment::Center),
+        Command::Size {
+            width: 2,
+            height: 2,
+        },
+        Command::line("RECEIPT"),
+        Command::Size {
+            width: 1,
+            height: 1,
+        },
+        Command::Align(Alignment::Right),
+        Command::Bold(true),
+        Command::line("Total: 8.50 <EUR>"),
+        Command::Bold(false),
+        Command::Align(Alignment::Left),
+        Command::Font(Font::B),
+        Command::line("0123456789012345678901234567890123456789ABCD"),
+        Command::Cut {
+            mode: CutMode::Full,
+            feed: None,
+        },
+    ]
+    .into()
+}
+
+#[test]
+fn text() {
+    let profile = Profile::get("XP-58").unwrap();
+    assert_eq!(
+        render::text(&receipt(), &profile),
+        "         R E C E I P T\n\
+         \x20              Total: 8.50 <EUR>\n\
+         0123456789012345678901234567890123456789AB\n\
+         CD\n\
+         - - - - - - - - - - - - - - - -\n"
+    );
+
+    // Rendered from the output of a printer
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        printer
+            .chain_align("ct")
+            .unwrap()
+            .chain_println("Hello")
+            .unwrap()
+            .chain_cut(CutMode::Full, None)
+            .unwrap()
+            .flush()
+            .unwrap();
+    }
+    let text = render::text(&decoder::document(&buf), &profile);
+    assert_eq!(
+        text,
+        "             Hello\n- - - - - - - - - - - - - - - -\n"
+    );
+}
+
+#[cfg(feature = "html")]
+#[test]
+fn html() {
+    let mut document = receipt();
+    document.push(Command::barcode("1234567", "EAN8", "BLW", "A", 2, 50));
+    let html = render::html(&document, &Profile::get("XP-58").unwrap());
+    assert!(
+        html.starts_with("<div style=\"font-family: monospace; white-space: pre; width: 32ch\">\n")
+    );
+    assert!(html.contains(
+        "<div style=\"text-align: right\"><span style=\"font-weight: bold\">Total: 8.50 &lt;EUR&gt;</span></div>\n"
+    ));
+    assert!(html.contains("<hr style=\"border: none; border-top: 1px dashed\">\n"));
+    assert!(html.contains("<img src=\"data:image/png;base64,"));
+    assert!(html.contains("\n12345670</div>\n"));
+}
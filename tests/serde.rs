@@ -0,0 +1,85 @@
+#![cfg(feature = "serde")]
+
+extern crate escposify;
+
+use escposify::command::{Command, Document, RasterMode};
+use escposify::img::Image;
+use escposify::printer::{CutMode, Printer};
+use image::{DynamicImage, GrayImage, Luma};
+
+#[test]
+fn roundtrip() {
+    let image = Image::from(DynamicImage::ImageLuma8(GrayImage::from_fn(
+        8,
+        2,
+        |x, _| Luma([if x % 2 == 0 { 0 } else { 255 }]),
+    )));
+    let document: Document = vec![
+        Command::Bold(true),
+        Command::line("Hello"),
+        Command::Raster {
+            image,
+            mode: RasterMode::Normal,
+        },
+        Command::Cut {
+            mode: CutMode::Partial,
+            feed: Some(3),
+        },
+    ]
+    .into();
+    let json = serde_json::to_string(&document).unwrap();
+    assert!(
+        json.starts_with(r#"[{"bold":true},{"text":"Hello\n"},{"raster":{"image":"iVBORw0KGgo"#)
+    );
+    let decoded: Document = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, document);
+}
+
+#[test]
+fn bridge_calls() {
+    let json = r#"[
+        {"name": "font", "args": ["C"]},
+        {"name": "align", "args": ["lt"]},
+        {"name": "style", "args": ["bu"]},
+        {"name": "size", "args": [0, 0]},
+        {"name": "text", "args": ["The quick brown fox jumps over the lazy dog"]},
+        {"name": "barcode", "args": ["12345678", "EAN8", "", "", 0, 0]},
+        {"name": "feed", "args": [1]},
+        {"name": "cut", "args": [false]}
+    ]"#;
+    let document: Document = serde_json::from_str(json).unwrap();
+
+    let mut expected = vec![];
+    {
+        let mut printer = Printer::new(&mut expected, None, None);
+        printer
+            .chain_font("C")
+            .unwrap()
+            .chain_align("lt")
+            .unwrap()
+            .chain_style("bu")
+            .unwrap()
+            .chain_size(0, 0)
+            .unwrap()
+            .chain_text("The quick brown fox jumps over the lazy dog")
+            .unwrap()
+            .chain_barcode("12345678", "EAN8", "", "", 0, 0)
+            .unwrap()
+            .chain_feed(4)
+            .unwrap()
+            .chain_cut(CutMode::Full, None)
+            .unwrap()
+            .flush()
+            .unwrap();
+    }
+    assert_eq!(document.encode().unwrap(), expected);
+
+    let unknown = r#"[{"name": "explode", "args": []}]"#;
+    assert!(serde_json::from_str::<Document>(unknown).is_err());
+    let out_of_range = r#"[{"name": "qrcode", "args": ["code", 259]}]"#;
+    assert!(serde_json::from_str::<Document>(out_of_range).is_err());
+    // Invalid commands report the command's error
+    let invalid = r#"[{"bold": "yes"}]"#;
+    let err = serde_json::from_str::<Document>(invalid).unwrap_err();
+    assert!(!err.to_string().contains("name"), "{}", err);
+}
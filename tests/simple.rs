@@ -3,7 +3,7 @@ extern crate tempfile;
 extern crate escposify;
 
 use escposify::device::File;
-use escposify::printer::Printer;
+use escposify::printer::{CutMode, Printer};
 use tempfile::NamedTempFileOptions;
 
 #[test]
@@ -30,7 +30,272 @@ fn simple() {
         .unwrap()
         .chain_feed(1)
         .unwrap()
-        .chain_cut(false)
+        .chain_cut(CutMode::Full, Some(0))
         .unwrap()
         .flush();
 }
+
+#[test]
+fn size() {
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        printer.size(3, 4).unwrap();
+        assert_eq!(printer.columns(), 16);
+        printer.chain_font("B").unwrap().size(0, 0).unwrap();
+        assert_eq!(printer.columns(), 64);
+        assert!(printer.size(9, 1).is_err());
+        printer.flush().unwrap();
+    }
+    assert_eq!(buf, b"\x1d\x21\x23\x1b\x4d\x01\x1d\x21\x00".to_vec());
+}
+
+#[test]
+fn text_style() {
+    use escposify::style::TextStyle;
+
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        printer
+            .apply_style(&TextStyle::new().bold(true).reverse(true).spacing(2))
+            .unwrap();
+        printer.flush().unwrap();
+    }
+    assert_eq!(buf, b"\x1b\x45\x01\x1d\x42\x01\x1b\x20\x02".to_vec());
+}
+
+#[test]
+fn scoped_style() {
+    use escposify::style::TextStyle;
+
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        printer.hwinit().unwrap();
+        printer.bold(false).unwrap(); // already off after ESC @
+        printer
+            .with_style(&TextStyle::new().bold(true).size(2, 2), |p| {
+                p.bold(true)?; // already on
+                p.print("X")
+            })
+            .unwrap();
+        assert_eq!(printer.state(), &TextStyle::plain());
+        printer.flush().unwrap();
+    }
+    assert_eq!(
+        buf,
+        b"\x1b\x40\x1d\x21\x11\x1b\x45\x01X\x1d\x21\x00\x1b\x45\x00".to_vec()
+    );
+}
+
+#[test]
+fn tab_stops() {
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        assert!(printer.set_tab_stops(&[8, 4]).is_err());
+        assert!(printer.set_tab_stops(&[1; 33]).is_err());
+        printer
+            .chain_set_tab_stops(&[10, 20])
+            .unwrap()
+            .chain_tab_row(&["Tea", "1", "2.00"])
+            .unwrap()
+            .flush()
+            .unwrap();
+    }
+    assert_eq!(buf, b"\x1b\x44\x0a\x14\x00Tea\x091\x092.00\n".to_vec());
+}
+
+#[test]
+fn print_position() {
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        assert_eq!(printer.mm_to_dots(72.0), 575);
+        assert!(printer.left_margin(600).is_err());
+        let margin = printer.mm_to_dots(4.0);
+        printer
+            .chain_left_margin(margin)
+            .unwrap()
+            .chain_print_area_width(512)
+            .unwrap()
+            .chain_position(300)
+            .unwrap()
+            .chain_relative_position(-2)
+            .unwrap()
+            .flush()
+            .unwrap();
+    }
+    assert_eq!(
+        buf,
+        b"\x1d\x4c\x20\x00\x1d\x57\x00\x02\x1b\x24\x2c\x01\x1b\x5c\xfe\xff".to_vec()
+    );
+}
+
+#[test]
+fn paper_feed() {
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        printer
+            .chain_feed(0)
+            .unwrap()
+            .chain_feed_dots(30)
+            .unwrap()
+            .chain_feed_lines(2)
+            .unwrap()
+            .chain_reverse_feed_lines(1)
+            .unwrap()
+            .chain_feed_to_cut()
+            .unwrap()
+            .flush()
+            .unwrap();
+    }
+    assert_eq!(
+        buf,
+        b"\x1b\x4a\x1e\x1b\x64\x02\x1b\x65\x01\x1c\x28\x4c\x02\x00\x42\x30".to_vec()
+    );
+}
+
+#[test]
+fn cut() {
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        printer
+            .chain_cut(CutMode::Partial, None)
+            .unwrap()
+            .chain_cut(CutMode::Full, Some(10))
+            .unwrap()
+            .chain_cut(CutMode::ReservedPartial, None)
+            .unwrap()
+            .chain_cut(CutMode::ReturnFull, Some(5))
+            .unwrap()
+            .flush()
+            .unwrap();
+    }
+    assert_eq!(
+        buf,
+        b"\x1d\x56\x01\x1d\x56\x41\x0a\x1d\x56\x62\x00\x1d\x56\x67\x05".to_vec()
+    );
+}
+
+#[test]
+fn cash_drawer() {
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        assert!(printer.cashdraw(3).is_err());
+        assert!(printer.drawer_pulse(2, 600, 100).is_err());
+        assert!(printer.drawer_pulse_realtime(5, 50).is_err());
+        printer
+            .chain_cashdraw(5)
+            .unwrap()
+            .chain_drawer_pulse(2, 100, 500)
+            .unwrap()
+            .chain_drawer_pulse_realtime(5, 200)
+            .unwrap()
+            .flush()
+            .unwrap();
+    }
+    assert_eq!(
+        buf,
+        b"\x1b\x70\x01\x19\xfa\x1b\x70\x00\x32\xfa\x10\x14\x01\x01\x02".to_vec()
+    );
+}
+
+#[test]
+fn macros() {
+    use std::time::Duration;
+
+    use escposify::printer::MacroMode;
+
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        let long = "x".repeat(2048);
+        assert!(printer.define_macro(|p| p.println(&long)).is_err());
+        printer
+            .chain_define_macro(|p| {
+                p.bold(true)?;
+                p.println("HEADER")
+            })
+            .unwrap()
+            .chain_run_macro(2, Duration::from_millis(500), MacroMode::Continuous)
+            .unwrap();
+        // The state after the macro is unknown
+        printer.bold(true).unwrap();
+        printer.flush().unwrap();
+    }
+    assert_eq!(
+        buf,
+        b"\x1d\x3a\x1b\x45\x01HEADER\n\x1d\x3a\x1d\x5e\x02\x05\x00\x1b\x45\x01".to_vec()
+    );
+}
+
+#[test]
+fn macro_changes_state() {
+    use std::time::Duration;
+
+    use escposify::printer::MacroMode;
+
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        printer
+            .chain_hwinit()
+            .unwrap()
+            .chain_define_macro(|p| p.bold(true))
+            .unwrap()
+            .chain_run_macro(1, Duration::ZERO, MacroMode::Continuous)
+            .unwrap();
+        // Not redundant, the macro left bold on
+        printer.bold(false).unwrap();
+        printer.flush().unwrap();
+    }
+    assert_eq!(
+        buf,
+        b"\x1b\x40\x1d\x3a\x1b\x45\x01\x1d\x3a\x1d\x5e\x01\x00\x00\x1b\x45\x00".to_vec()
+    );
+}
+
+#[test]
+fn macro_scope() {
+    use encoding::all::WINDOWS_1252;
+    use escposify::command::Command;
+
+    let mut buf = vec![];
+    {
+        let mut printer = Printer::new(&mut buf, None, None);
+        printer
+            .chain_define_macro(|p| {
+                p.set_codec(WINDOWS_1252)?;
+                p.kanji_encoding("jis")?;
+                p.print("a")
+            })
+            .unwrap();
+        // Codec and Kanji encoding are back to their previous values
+        printer.print("ä").unwrap();
+        printer.kanji_encoding("jis").unwrap();
+        let nested = printer.define_macro(|p| p.command(Command::DefineMacro(vec![])));
+        assert!(nested.is_err());
+        printer.flush().unwrap();
+    }
+    assert_eq!(
+        buf,
+        b"\x1d\x3a\x1b\x74\x10\x1c\x43\x00a\x1d\x3a\xc3\xa4\x1c\x43\x00".to_vec()
+    );
+}
+
+#[cfg(feature = "qrcode")]
+#[test]
+fn qrcode_range() {
+    use std::io;
+
+    let mut printer = Printer::new(io::sink(), None, None);
+    let err = printer.qrcode("code", Some(259), "L", None).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = printer.qrcode("code", None, "L", Some(-1)).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,70 @@
+extern crate escposify;
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use escposify::device::Network;
+use escposify::printer::{CutMode, Printer};
+use escposify::profile::Profile;
+use escposify::simulator::{Simulator, Status};
+
+fn printer(simulator: &Simulator) -> Printer<Network> {
+    let mut network = Network::new("127.0.0.1", simulator.local_addr().port()).unwrap();
+    network
+        .set_read_timeout(Some(Duration::from_secs(1)))
+        .unwrap();
+    Printer::new(network, None, None)
+}
+
+#[test]
+fn queries_and_jobs() {
+    let simulator = Simulator::start("127.0.0.1:0").unwrap();
+    simulator.set_status(Status {
+        drawer_open: true,
+        ..Status::default()
+    });
+    {
+        let mut printer = printer(&simulator);
+        let info = printer.printer_info().unwrap();
+        assert_eq!(info, simulator.info());
+        assert_eq!(info.model_name.as_deref(), Some("Simulator"));
+        assert!(printer.drawer_open().unwrap());
+        printer
+            .chain_println("Hello")
+            .unwrap()
+            .chain_cut(CutMode::Full, None)
+            .unwrap()
+            .flush()
+            .unwrap();
+    }
+
+    let jobs = simulator.wait_for_jobs(1, Some(Duration::from_secs(5)));
+    assert_eq!(jobs.len(), 1);
+    assert!(jobs[0].text(&Profile::default()).contains("Hello"));
+}
+
+#[test]
+fn offline() {
+    let simulator = Simulator::start("127.0.0.1:0").unwrap();
+    simulator.set_status(Status {
+        cover_open: true,
+        ..Status::default()
+    });
+    let mut stream = TcpStream::connect(simulator.local_addr()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_millis(500)))
+        .unwrap();
+    let mut buf = [0; 1];
+
+    // Only real-time status is answered while offline, even when split
+    stream.write_all(b"\x10").unwrap();
+    stream.flush().unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    stream.write_all(b"\x04\x02").unwrap();
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [0x16]);
+
+    stream.write_all(b"\x1d\x49\x01").unwrap();
+    assert!(stream.read(&mut buf).is_err());
+}